@@ -0,0 +1,326 @@
+use crate::{Error, Result};
+use serde::de::DeserializeOwned;
+use std::fmt::Display;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::str::FromStr;
+use toml::{Table, Value};
+
+const CONFIG_FILE: &str = "aoc.toml";
+const INPUT_FILE: &str = "input.txt";
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+pub enum Format {
+    #[default]
+    Text,
+    Json,
+}
+
+impl FromStr for Format {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "text" => Ok(Self::Text),
+            "json" => Ok(Self::Json),
+            _ => Err(Error::Args(format!("unknown format `{s}`"))),
+        }
+    }
+}
+
+/// Runner settings for a single day, read from `aoc.toml` and overridden by CLI flags.
+///
+/// Top-level `input_dir` and `format` keys apply to every day; a `[day-N]` table
+/// may override them, set an explicit `input` file, and hold the day's own parameters.
+#[derive(Debug)]
+pub struct Config {
+    day: String,
+    input_dir: PathBuf,
+    input: Option<PathBuf>,
+    format: Format,
+    params: Table,
+    repl: bool,
+}
+
+#[derive(Debug, Default)]
+struct Args {
+    config: Option<PathBuf>,
+    repl: bool,
+    input_dir: Option<PathBuf>,
+    input: Option<PathBuf>,
+    format: Option<Format>,
+    /// `--set` values in order, keyed by their dotted path.
+    params: Vec<(String, Value)>,
+}
+
+impl Args {
+    fn value(args: &mut impl Iterator<Item = String>, flag: &str) -> Result<String> {
+        args.next()
+            .ok_or_else(|| Error::Args(format!("missing value for `{flag}`")))
+    }
+
+    fn parse(args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let mut res = Self::default();
+        let mut args = args.into_iter();
+        while let Some(flag) = args.next() {
+            match flag.as_str() {
+                "--config" => res.config = Some(Self::value(&mut args, &flag)?.into()),
+                "--repl" => res.repl = true,
+                "--input-dir" => res.input_dir = Some(Self::value(&mut args, &flag)?.into()),
+                "--input" => res.input = Some(Self::value(&mut args, &flag)?.into()),
+                "--format" => res.format = Some(Self::value(&mut args, &flag)?.parse()?),
+                "--set" => {
+                    let param = Self::value(&mut args, &flag)?;
+                    let (key, value) = param
+                        .split_once('=')
+                        .ok_or_else(|| Error::Args(format!("expected KEY=VALUE, got `{param}`")))?;
+                    res.params
+                        .push((key.trim().to_string(), param_value(value.trim())));
+                }
+                _ => return Err(Error::Args(format!("unknown argument `{flag}`"))),
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// Reads a CLI value as TOML when possible (`12`, `true`, `"AAA"`), as a bare string otherwise.
+fn param_value(s: &str) -> Value {
+    format!("value = {s}")
+        .parse::<Table>()
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| Value::String(s.to_string()))
+}
+
+impl Config {
+    pub fn load(day: &str) -> Result<Self> {
+        Self::from_args(day, std::env::args().skip(1))
+    }
+
+    pub fn from_args(day: &str, args: impl IntoIterator<Item = String>) -> Result<Self> {
+        let args = Args::parse(args)?;
+        let path = args.config.clone().unwrap_or(PathBuf::from(CONFIG_FILE));
+        let table = match std::fs::read_to_string(&path) {
+            Ok(text) => text
+                .parse::<Table>()
+                .map_err(|source| Error::Toml { path, source })?,
+            Err(e) if e.kind() == ErrorKind::NotFound && args.config.is_none() => Table::new(),
+            Err(source) => return Err(Error::Io { path, source }),
+        };
+
+        let mut config = Self::from_table(day, table)?;
+        config.apply(args)?;
+        Ok(config)
+    }
+
+    fn from_table(day: &str, mut table: Table) -> Result<Self> {
+        let mut params = match table.remove(day) {
+            Some(Value::Table(t)) => t,
+            Some(_) => return Err(Error::Args(format!("`{day}` must be a table"))),
+            None => Table::new(),
+        };
+
+        let mut setting = |key: &str| -> Result<Option<String>> {
+            let value = params.remove(key).or_else(|| table.remove(key));
+            value
+                .map(|v| v.try_into())
+                .transpose()
+                .map_err(|source| Error::Param {
+                    key: key.to_string(),
+                    source,
+                })
+        };
+
+        let input_dir = setting("input_dir")?.unwrap_or(".".to_string()).into();
+        let input = setting("input")?.map(PathBuf::from);
+        let format = setting("format")?
+            .map(|f| f.parse())
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(Self {
+            day: day.to_string(),
+            input_dir,
+            input,
+            format,
+            params,
+            repl: false,
+        })
+    }
+
+    /// Overrides settings with CLI flags. A dotted key such as `limits.red` sets one
+    /// entry of a nested table, leaving the rest of it alone.
+    fn apply(&mut self, args: Args) -> Result<()> {
+        if let Some(dir) = args.input_dir {
+            self.input_dir = dir;
+        }
+        if let Some(input) = args.input {
+            self.input = Some(input);
+        }
+        if let Some(format) = args.format {
+            self.format = format;
+        }
+        for (key, value) in args.params {
+            let mut path: Vec<&str> = key.split('.').collect();
+            let last = path.pop().unwrap_or_default();
+            let mut table = &mut self.params;
+            for name in path {
+                let entry = table
+                    .entry(name)
+                    .or_insert_with(|| Value::Table(Table::new()));
+                table = entry
+                    .as_table_mut()
+                    .ok_or_else(|| Error::Args(format!("`{name}` in `{key}` is not a table")))?;
+            }
+            table.insert(last.to_string(), value);
+        }
+        self.repl = args.repl;
+        Ok(())
+    }
+
+    pub fn format(&self) -> Format {
+        self.format
+    }
+
+    /// Whether the day should start an interactive session instead of solving.
+    pub fn repl(&self) -> bool {
+        self.repl
+    }
+
+    pub fn input_path(&self) -> PathBuf {
+        self.input
+            .clone()
+            .unwrap_or_else(|| self.input_dir.join(&self.day).join(INPUT_FILE))
+    }
+
+    pub fn input(&self) -> Result<String> {
+        let path = self.input_path();
+        std::fs::read_to_string(&path).map_err(|source| Error::Io { path, source })
+    }
+
+    /// Like `input`, but falls back to `default` when no input was configured and the
+    /// default input file does not exist.
+    pub fn input_or(&self, default: &str) -> Result<String> {
+        if self.input.is_none() && !self.input_path().exists() {
+            return Ok(default.to_string());
+        }
+        self.input()
+    }
+
+    pub fn param<T: DeserializeOwned>(&self, key: &str) -> Result<Option<T>> {
+        self.params
+            .get(key)
+            .map(|v| v.clone().try_into())
+            .transpose()
+            .map_err(|source| Error::Param {
+                key: key.to_string(),
+                source,
+            })
+    }
+
+    pub fn param_or<T: DeserializeOwned>(&self, key: &str, default: T) -> Result<T> {
+        Ok(self.param(key)?.unwrap_or(default))
+    }
+
+    pub fn answer(&self, part: u32, value: impl Display) {
+        match self.format {
+            Format::Text => println!("Part {part}: {value}"),
+            Format::Json => {
+                let value = value.to_string().replace('\\', "\\\\").replace('"', "\\\"");
+                println!(
+                    r#"{{"day":"{}","part":{part},"answer":"{value}"}}"#,
+                    self.day
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"
+    input_dir = "inputs"
+    format = "json"
+
+    [day-2]
+    red = 12
+    green = 13
+
+    [day-2.limits]
+    red = 12
+    blue = 14
+
+    [day-8]
+    start = "AAA"
+    input = "day-8/other.txt"
+    format = "text"
+    "#;
+
+    fn split(s: &str) -> Vec<String> {
+        s.split_whitespace().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_table() {
+        let config = Config::from_table("day-2", SAMPLE.parse().unwrap()).unwrap();
+        assert_eq!(config.input_path(), PathBuf::from("inputs/day-2/input.txt"));
+        assert_eq!(config.format(), Format::Json);
+        assert_eq!(config.param_or("red", 0).unwrap(), 12);
+        assert_eq!(config.param_or("blue", 14).unwrap(), 14);
+        assert!(config.param::<char>("red").is_err());
+
+        let config = Config::from_table("day-8", SAMPLE.parse().unwrap()).unwrap();
+        assert_eq!(config.input_path(), PathBuf::from("day-8/other.txt"));
+        assert_eq!(config.format(), Format::Text);
+        assert_eq!(config.param::<String>("start").unwrap().unwrap(), "AAA");
+    }
+
+    #[test]
+    fn test_args_override() {
+        let mut config = Config::from_table("day-2", SAMPLE.parse().unwrap()).unwrap();
+        let args = Args::parse(split(
+            "--input-dir . --format text --set red=20 --repl --set name=xy",
+        ))
+        .unwrap();
+        config.apply(args).unwrap();
+
+        assert_eq!(config.input_path(), PathBuf::from("./day-2/input.txt"));
+        assert_eq!(config.format(), Format::Text);
+        assert!(config.repl());
+        assert_eq!(config.param_or("red", 0).unwrap(), 20);
+        assert_eq!(config.param_or("green", 0).unwrap(), 13);
+        assert_eq!(config.param::<String>("name").unwrap().unwrap(), "xy");
+
+        assert!(Args::parse(split("--set red")).is_err());
+        let args = Args::parse(split("--set red.dark=1")).unwrap();
+        assert!(config.apply(args).is_err());
+        assert!(Args::parse(split("--verbose")).is_err());
+    }
+
+    #[test]
+    fn test_dotted_override() {
+        let limits = |config: &Config| {
+            let limits: Table = config.param("limits").unwrap().unwrap();
+            limits.to_string()
+        };
+
+        let mut config = Config::from_table("day-2", SAMPLE.parse().unwrap()).unwrap();
+        let args = Args::parse(split("--set limits.red=20 --set limits.green=3")).unwrap();
+        config.apply(args).unwrap();
+        assert_eq!(limits(&config), "blue = 14\ngreen = 3\nred = 20\n");
+
+        let args = Args::parse(split("--set limits={red=1}")).unwrap();
+        config.apply(args).unwrap();
+        assert_eq!(limits(&config), "red = 1\n");
+
+        let args = Args::parse(split("--set new.depth.key=1")).unwrap();
+        config.apply(args).unwrap();
+        assert_eq!(
+            config.param::<Table>("new").unwrap().unwrap().to_string(),
+            "[depth]\nkey = 1\n"
+        );
+    }
+}
@@ -0,0 +1,31 @@
+use std::path::PathBuf;
+
+mod config;
+pub mod repl;
+
+pub use config::{Config, Format};
+
+#[derive(Debug, thiserror::Error)]
+pub enum Error {
+    #[error("reading {path}: {source}")]
+    Io {
+        path: PathBuf,
+        source: std::io::Error,
+    },
+    #[error("parsing {path}: {source}")]
+    Toml {
+        path: PathBuf,
+        source: toml::de::Error,
+    },
+    #[error("invalid parameter `{key}`: {source}")]
+    Param {
+        key: String,
+        source: toml::de::Error,
+    },
+    #[error("invalid argument: {0}")]
+    Args(String),
+    #[error("console: {0}")]
+    Console(std::io::Error),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
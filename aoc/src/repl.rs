@@ -0,0 +1,96 @@
+use crate::{Error, Result};
+use std::fmt::Display;
+use std::io::{BufRead, Write};
+
+/// Runs an interactive session on stdin/stdout until EOF or `quit`.
+///
+/// Every line is split on whitespace and handed to `eval` as a command and its
+/// arguments; `help` prints `usage`. Errors from `eval` are reported and the
+/// session carries on.
+pub fn run<E, F>(prompt: &str, usage: &str, eval: F) -> Result<()>
+where
+    E: Display,
+    F: FnMut(&str, &[&str]) -> std::result::Result<String, E>,
+{
+    let stdin = std::io::stdin();
+    run_with(stdin.lock(), std::io::stdout(), prompt, usage, eval)
+}
+
+pub fn run_with<R, W, E, F>(input: R, output: W, prompt: &str, usage: &str, eval: F) -> Result<()>
+where
+    R: BufRead,
+    W: Write,
+    E: Display,
+    F: FnMut(&str, &[&str]) -> std::result::Result<String, E>,
+{
+    session(input, output, prompt, usage, eval).map_err(Error::Console)
+}
+
+/// The session itself; only reading and writing the console can fail.
+fn session<R, W, E, F>(
+    input: R,
+    mut output: W,
+    prompt: &str,
+    usage: &str,
+    mut eval: F,
+) -> std::io::Result<()>
+where
+    R: BufRead,
+    W: Write,
+    E: Display,
+    F: FnMut(&str, &[&str]) -> std::result::Result<String, E>,
+{
+    let mut lines = input.lines();
+    loop {
+        write!(output, "{prompt}> ")?;
+        output.flush()?;
+
+        let Some(line) = lines.next().transpose()? else {
+            writeln!(output)?;
+            break;
+        };
+
+        let words: Vec<&str> = line.split_whitespace().collect();
+        match words.split_first() {
+            None => continue,
+            Some((&"quit" | &"exit", _)) => break,
+            Some((&"help", _)) => writeln!(output, "{usage}")?,
+            Some((cmd, args)) => match eval(cmd, args) {
+                Ok(res) => writeln!(output, "{res}")?,
+                Err(e) => writeln!(output, "error: {e}")?,
+            },
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_session() {
+        let input = "double 21\n\nhelp\nnope\nquit\ndouble 1\n";
+        let mut output = Vec::new();
+        run_with(
+            input.as_bytes(),
+            &mut output,
+            "t",
+            "double N",
+            |cmd, args| match (cmd, args) {
+                ("double", [n]) => n
+                    .parse::<u32>()
+                    .map(|n| (n * 2).to_string())
+                    .map_err(|e| e.to_string()),
+                _ => Err(format!("unknown command `{cmd}`")),
+            },
+        )
+        .unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        assert_eq!(
+            output,
+            "t> 42\nt> t> double N\nt> error: unknown command `nope`\nt> "
+        );
+    }
+}
@@ -1,60 +1,596 @@
+use automaton::Automaton;
+use std::fmt;
+use std::ops::Range;
+use std::str::FromStr;
+
 const NUMS_ENG: [&str; 10] = [
     "zero", "one", "two", "three", "four", "five", "six", "seven", "eight", "nine",
 ];
 
+const NUMS_SPA: [&str; 10] = [
+    "cero", "uno", "dos", "tres", "cuatro", "cinco", "seis", "siete", "ocho", "nueve",
+];
+
+const NUMS_DEU: [&str; 10] = [
+    "null", "eins", "zwei", "drei", "vier", "fünf", "sechs", "sieben", "acht", "neun",
+];
+
+const NUMS_FRA: [&str; 10] = [
+    "zéro", "un", "deux", "trois", "quatre", "cinq", "six", "sept", "huit", "neuf",
+];
+
+const TEENS_ENG: [&str; 10] = [
+    "ten",
+    "eleven",
+    "twelve",
+    "thirteen",
+    "fourteen",
+    "fifteen",
+    "sixteen",
+    "seventeen",
+    "eighteen",
+    "nineteen",
+];
+
+const TENS_ENG: [&str; 8] = [
+    "twenty", "thirty", "forty", "fifty", "sixty", "seventy", "eighty", "ninety",
+];
+
+/// Zero of every run of decimal digits (Unicode category Nd) as of Unicode 15;
+/// each run holds the values 0 to 9 in order.
+const DIGIT_ZEROS: [u32; 68] = [
+    0x0030, 0x0660, 0x06F0, 0x07C0, 0x0966, 0x09E6, 0x0A66, 0x0AE6, 0x0B66, 0x0BE6, 0x0C66, 0x0CE6,
+    0x0D66, 0x0DE6, 0x0E50, 0x0ED0, 0x0F20, 0x1040, 0x1090, 0x17E0, 0x1810, 0x1946, 0x19D0, 0x1A80,
+    0x1A90, 0x1B50, 0x1BB0, 0x1C40, 0x1C50, 0xA620, 0xA8D0, 0xA900, 0xA9D0, 0xA9F0, 0xAA50, 0xABF0,
+    0xFF10, 0x104A0, 0x10D30, 0x11066, 0x110F0, 0x11136, 0x111D0, 0x112F0, 0x11450, 0x114D0,
+    0x11650, 0x116C0, 0x11730, 0x118E0, 0x11950, 0x11C50, 0x11D50, 0x11DA0, 0x11F50, 0x16A60,
+    0x16AC0, 0x16B50, 0x1D7CE, 0x1D7D8, 0x1D7E2, 0x1D7EC, 0x1D7F6, 0x1E140, 0x1E2F0, 0x1E4F0,
+    0x1E950, 0x1FBF0,
+];
+
 fn main() {
-    let text = std::fs::read_to_string("day-1/input.txt").unwrap();
-    let sum = process(&text);
-    println!("Answer: {sum}");
+    let config = aoc::Config::load("day-1").unwrap();
+    let text = config.input().unwrap();
+    let only: Option<Mode> = config
+        .param::<String>("mode")
+        .unwrap()
+        .map(|m| m.parse().unwrap());
+
+    let mut calibration = Calibration::new(Mode::default());
+    if let Some(languages) = config.param::<Vec<String>>("languages").unwrap() {
+        calibration.vocabularies = languages
+            .iter()
+            .map(|l| Vocabulary::builtin(l).unwrap())
+            .collect();
+    }
+    if let Some(path) = config.param::<String>("vocabulary").unwrap() {
+        let text = std::fs::read_to_string(path).unwrap();
+        calibration
+            .vocabularies
+            .extend(Vocabulary::parse(&text).unwrap());
+    }
+
+    let report = config.param_or("report", false).unwrap();
+
+    let modes = only.map_or(vec![Mode::Digits, Mode::Words], |m| vec![m]);
+    for mode in modes {
+        calibration.mode = mode;
+        if report {
+            let matcher = Matcher::new(&calibration);
+            for line in lines(&text) {
+                println!("{}", matcher.report(line));
+            }
+        }
+        config.answer(mode.part(), process(&text, &calibration));
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+enum Mode {
+    /// Only numeric digits count.
+    Digits,
+    /// Numeric digits and spelled-out words such as "one".
+    #[default]
+    Words,
+    /// As `Words`, plus English numbers up to 999 such as "twelve" or "forty-two".
+    ///
+    /// A compound number contributes its leading digit when it is the first match
+    /// of a line and its trailing digit when it is the last, so "forty-two" alone
+    /// calibrates to 42 and "one hundred" to 10.
+    Compound,
 }
 
-struct AocDigit<'a> {
-    inner: &'a str,
+impl Mode {
+    /// The puzzle part a mode answers; the extended modes stand in for part 2.
+    fn part(&self) -> u32 {
+        match self {
+            Self::Digits => 1,
+            Self::Words | Self::Compound => 2,
+        }
+    }
 }
 
-impl<'a> Iterator for AocDigit<'a> {
-    type Item = u32;
+impl FromStr for Mode {
+    type Err = String;
 
-    fn next(&mut self) -> Option<Self::Item> {
-        let chars = self.inner.chars();
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "digits" => Ok(Self::Digits),
+            "words" => Ok(Self::Words),
+            "compound" => Ok(Self::Compound),
+            _ => Err(format!("unknown calibration mode `{s}`")),
+        }
+    }
+}
+
+/// Spelled-out digits of one language, where `words[i]` has the value `i`.
+#[derive(Clone, Debug, PartialEq)]
+struct Vocabulary {
+    language: String,
+    words: Vec<String>,
+}
+
+impl Vocabulary {
+    fn new(language: &str, words: &[&str]) -> Self {
+        Self {
+            language: language.to_string(),
+            words: words.iter().map(|w| w.to_lowercase()).collect(),
+        }
+    }
+
+    fn builtin(language: &str) -> Result<Self, String> {
+        let words = match language {
+            "english" => NUMS_ENG,
+            "spanish" => NUMS_SPA,
+            "german" => NUMS_DEU,
+            "french" => NUMS_FRA,
+            _ => return Err(format!("no built-in vocabulary for `{language}`")),
+        };
+        Ok(Self::new(language, &words))
+    }
 
-        for c in chars {
-            let sub = self.inner;
-            if let Some(substr) = self.inner.get(1..) {
-                self.inner = substr;
+    /// Reads custom vocabularies, one per line as `language: zero one two ...`.
+    fn parse(text: &str) -> Result<Vec<Self>, String> {
+        text.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty() && !l.starts_with('#'))
+            .map(|l| {
+                let (language, words) = l
+                    .split_once(':')
+                    .ok_or_else(|| format!("missing language in `{l}`"))?;
+                let words: Vec<&str> = words.split_whitespace().collect();
+                if words.is_empty() || words.len() > 10 {
+                    return Err(format!("expected 1 to 10 words for `{language}`"));
+                }
+                Ok(Self::new(language.trim(), &words))
+            })
+            .collect()
+    }
+}
+
+/// English spellings of `n` below a thousand, with hyphenated tens and "hundred"
+/// followed by the rest with or without "and".
+fn spell_english(n: u32) -> Vec<String> {
+    match n {
+        0..=9 => vec![NUMS_ENG[n as usize].to_string()],
+        10..=19 => vec![TEENS_ENG[n as usize - 10].to_string()],
+        20..=99 => {
+            let tens = TENS_ENG[n as usize / 10 - 2];
+            match n % 10 {
+                0 => vec![tens.to_string()],
+                ones => vec![format!("{tens}-{}", NUMS_ENG[ones as usize])],
+            }
+        }
+        _ => {
+            let hundreds = format!("{} hundred", NUMS_ENG[n as usize / 100]);
+            match n % 100 {
+                0 => vec![hundreds],
+                rest => spell_english(rest)
+                    .into_iter()
+                    .flat_map(|r| [format!("{hundreds} and {r}"), format!("{hundreds} {r}")])
+                    .collect(),
             }
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct Calibration {
+    mode: Mode,
+    vocabularies: Vec<Vocabulary>,
+}
+
+impl Calibration {
+    fn new(mode: Mode) -> Self {
+        Self {
+            mode,
+            vocabularies: vec![Vocabulary::new("english", &NUMS_ENG)],
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+enum Source<'v> {
+    Digit,
+    /// A spelled-out word, with the language of the vocabulary it came from.
+    Word(&'v str),
+}
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+struct Match<'v> {
+    value: u32,
+    source: Source<'v>,
+}
+
+impl Match<'_> {
+    fn leading(&self) -> u32 {
+        let mut value = self.value;
+        while value >= 10 {
+            value /= 10;
+        }
+        value
+    }
+
+    fn trailing(&self) -> u32 {
+        self.value % 10
+    }
+}
+
+/// The value of a decimal digit from any script, such as '٣' or '３'.
+fn decimal(c: char) -> Option<u32> {
+    let c = c as u32;
+    let run = DIGIT_ZEROS.partition_point(|zero| *zero <= c);
+    let value = c - DIGIT_ZEROS[run.checked_sub(1)?];
+    (value < 10).then_some(value)
+}
+
+/// Lowercases a char for matching, keeping it as is when its lowercase form is
+/// several chars or a different UTF-8 length, so match spans stay valid offsets
+/// into the original line.
+fn fold(c: char) -> char {
+    let mut lower = c.to_lowercase();
+    match (lower.next(), lower.next()) {
+        (Some(l), None) if l.len_utf8() == c.len_utf8() => l,
+        _ => c,
+    }
+}
 
-            if let Some(digit) = c.to_digit(10) {
-                return Some(digit);
+/// A vocabulary word compiled into the matcher; `len` is in bytes.
+#[derive(Debug)]
+struct Word<'v> {
+    value: u32,
+    language: &'v str,
+    len: usize,
+}
+
+/// Calibration patterns compiled once: every vocabulary word, matched forwards to
+/// find the first digit and on the reversed line to find the last one.
+#[derive(Debug)]
+struct Matcher<'v> {
+    mode: Mode,
+    words: Vec<Word<'v>>,
+    forward: Automaton,
+    backward: Automaton,
+    longest: usize,
+}
+
+impl<'v> Matcher<'v> {
+    fn new(calibration: &'v Calibration) -> Self {
+        let mut words: Vec<(u32, &str, String)> = match calibration.mode {
+            Mode::Digits => Vec::new(),
+            Mode::Words | Mode::Compound => calibration
+                .vocabularies
+                .iter()
+                .flat_map(|v| {
+                    v.words
+                        .iter()
+                        .enumerate()
+                        .map(move |(i, w)| (i as u32, v.language.as_str(), w.clone()))
+                })
+                .collect(),
+        };
+        if calibration.mode == Mode::Compound {
+            // Puzzle lines have no separators, so also match each spelling run together.
+            for n in 10..1000 {
+                for w in spell_english(n) {
+                    if w.contains([' ', '-']) {
+                        words.push((n, "english", w.replace([' ', '-'], "")));
+                    }
+                    words.push((n, "english", w));
+                }
             }
+        }
+
+        Self {
+            mode: calibration.mode,
+            forward: Automaton::new(words.iter().map(|(_, _, w)| w.chars())),
+            backward: Automaton::new(words.iter().map(|(_, _, w)| w.chars().rev())),
+            longest: words.iter().map(|(_, _, w)| w.len()).fold(1, usize::max),
+            words: words
+                .iter()
+                .map(|(value, language, w)| Word {
+                    value: *value,
+                    language,
+                    len: w.len(),
+                })
+                .collect(),
+        }
+    }
+
+    fn digit(c: char) -> Option<Match<'v>> {
+        decimal(c).map(|value| Match {
+            value,
+            source: Source::Digit,
+        })
+    }
 
-            for (i, w) in NUMS_ENG.iter().enumerate() {
-                if sub.starts_with(w) {
-                    return Some(i as u32);
+    fn word(&self, pattern: usize) -> (usize, Match<'v>) {
+        let w = &self.words[pattern];
+        let m = Match {
+            value: w.value,
+            source: Source::Word(w.language),
+        };
+        (w.len, m)
+    }
+
+    fn scan<'a, 'm>(&'m self, line: &'a str) -> AocDigit<'a, 'm, 'v> {
+        AocDigit {
+            matcher: self,
+            chars: line.char_indices(),
+            end: 0,
+            state: Automaton::START,
+            pending: &[],
+        }
+    }
+
+    /// The leftmost match, preferring the longest one when several start together.
+    fn first(&self, line: &str) -> Option<Located<'v>> {
+        let mut best: Option<(Range<usize>, Match<'v>)> = None;
+        for (span, m) in self.scan(line) {
+            if let Some((b, _)) = &best {
+                if span.end - b.start > self.longest {
+                    break;
+                }
+                if span.start > b.start || (span.start == b.start && span.len() <= b.len()) {
+                    continue;
                 }
             }
+            best = Some((span, m));
         }
+        best
+    }
 
+    /// The rightmost match, walking the line backwards so only its tail is read.
+    fn last(&self, line: &str) -> Option<Located<'v>> {
+        let mut state = Automaton::START;
+        for (i, c) in line.char_indices().rev() {
+            let mut best = Self::digit(c).map(|m| (c.len_utf8(), m));
+            if self.mode != Mode::Digits {
+                state = self.backward.step(state, fold(c));
+                for pattern in self.backward.matches(state) {
+                    let (len, m) = self.word(*pattern);
+                    if best.is_none_or(|(l, _)| len > l) {
+                        best = Some((len, m));
+                    }
+                }
+            }
+            if let Some((len, m)) = best {
+                return Some((i..i + len, m));
+            }
+        }
         None
     }
+
+    fn report<'l>(&self, line: &'l str) -> Report<'l, 'v> {
+        Report {
+            line,
+            first: self.first(line),
+            last: self.last(line),
+        }
+    }
+}
+
+/// A match with the byte range it covers in its line.
+type Located<'v> = (Range<usize>, Match<'v>);
+
+/// How a line calibrates: the matches supplying its first and last digit.
+#[derive(Clone, Debug, PartialEq)]
+struct Report<'l, 'v> {
+    line: &'l str,
+    first: Option<Located<'v>>,
+    last: Option<Located<'v>>,
+}
+
+impl Report<'_, '_> {
+    /// Lines without any digit calibrate to 0.
+    fn has_digit(&self) -> bool {
+        self.first.is_some()
+    }
+
+    fn value(&self) -> u32 {
+        let lhs = self.first.as_ref().map_or(0, |(_, m)| m.leading());
+        let rhs = self.last.as_ref().map_or(lhs, |(_, m)| m.trailing());
+        lhs * 10 + rhs
+    }
+
+    /// The line with its first and last match in brackets; overlapping matches
+    /// share a pair, as in "[eightwo]".
+    fn render(&self) -> String {
+        let mut spans: Vec<Range<usize>> = [&self.first, &self.last]
+            .into_iter()
+            .flatten()
+            .map(|(span, _)| span.clone())
+            .collect();
+        if let [a, b] = spans.as_mut_slice() {
+            if b.start < a.end {
+                a.end = a.end.max(b.end);
+                spans.pop();
+            }
+        }
+
+        let mut out = String::new();
+        let mut at = 0;
+        for span in spans {
+            out.push_str(&self.line[at..span.start]);
+            out.push('[');
+            out.push_str(&self.line[span.clone()]);
+            out.push(']');
+            at = span.end;
+        }
+        out.push_str(&self.line[at..]);
+        out
+    }
+}
+
+impl fmt::Display for Report<'_, '_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:>3}  {}", self.value(), self.render())?;
+        if !self.has_digit() {
+            write!(f, "  (no digit)")?;
+        }
+        for (name, located) in [("first", &self.first), ("last", &self.last)] {
+            if let Some((span, m)) = located {
+                let source = match m.source {
+                    Source::Digit => "digit".to_string(),
+                    Source::Word(language) => format!("{language} word"),
+                };
+                write!(f, "  {name}: {} at {span:?} ({source})", m.value)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Every match in a line with its byte span, overlapping ones included, in the
+/// order they end.
+struct AocDigit<'a, 'm, 'v> {
+    matcher: &'m Matcher<'v>,
+    chars: std::str::CharIndices<'a>,
+    end: usize,
+    state: usize,
+    pending: &'m [usize],
+}
+
+impl<'v> Iterator for AocDigit<'_, '_, 'v> {
+    type Item = (Range<usize>, Match<'v>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((pattern, rest)) = self.pending.split_first() {
+                self.pending = rest;
+                let (len, m) = self.matcher.word(*pattern);
+                return Some((self.end - len..self.end, m));
+            }
+
+            let (i, c) = self.chars.next()?;
+            self.end = i + c.len_utf8();
+            if self.matcher.mode != Mode::Digits {
+                let automaton = &self.matcher.forward;
+                self.state = automaton.step(self.state, fold(c));
+                self.pending = automaton.matches(self.state);
+            }
+            if let Some(m) = Matcher::digit(c) {
+                return Some((i..self.end, m));
+            }
+        }
+    }
+}
+
+mod automaton {
+    use std::collections::{HashMap, VecDeque};
+
+    #[derive(Debug, Default)]
+    struct State {
+        next: HashMap<char, usize>,
+        fail: usize,
+        /// Patterns ending in this state, including those reached through failure links.
+        out: Vec<usize>,
+    }
+
+    /// Aho-Corasick automaton over chars. Stepping it through a text one char at a
+    /// time reports every pattern that ends at that char, overlapping ones included.
+    #[derive(Debug)]
+    pub struct Automaton {
+        states: Vec<State>,
+    }
+
+    impl Automaton {
+        pub const START: usize = 0;
+
+        pub fn new<P, I>(patterns: P) -> Self
+        where
+            P: IntoIterator<Item = I>,
+            I: IntoIterator<Item = char>,
+        {
+            let mut states = vec![State::default()];
+            for (i, pattern) in patterns.into_iter().enumerate() {
+                let mut state = Self::START;
+                for c in pattern {
+                    state = match states[state].next.get(&c) {
+                        Some(next) => *next,
+                        None => {
+                            states.push(State::default());
+                            let next = states.len() - 1;
+                            states[state].next.insert(c, next);
+                            next
+                        }
+                    };
+                }
+                states[state].out.push(i);
+            }
+
+            let mut automaton = Self { states };
+            let mut queue: VecDeque<usize> = automaton.states[Self::START]
+                .next
+                .values()
+                .copied()
+                .collect();
+            while let Some(state) = queue.pop_front() {
+                let edges: Vec<(char, usize)> = automaton.states[state]
+                    .next
+                    .iter()
+                    .map(|(c, n)| (*c, *n))
+                    .collect();
+                for (c, next) in edges {
+                    let fail = automaton.step(automaton.states[state].fail, c);
+                    let inherited = automaton.states[fail].out.clone();
+                    automaton.states[next].fail = fail;
+                    automaton.states[next].out.extend(inherited);
+                    queue.push_back(next);
+                }
+            }
+            automaton
+        }
+
+        pub fn step(&self, mut state: usize, c: char) -> usize {
+            loop {
+                if let Some(next) = self.states[state].next.get(&c) {
+                    return *next;
+                }
+                if state == Self::START {
+                    return state;
+                }
+                state = self.states[state].fail;
+            }
+        }
+
+        pub fn matches(&self, state: usize) -> &[usize] {
+            &self.states[state].out
+        }
+    }
+}
+
+fn lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines().map(|l| l.trim()).filter(|l| !l.is_empty())
 }
 
-fn process_line(line: &str) -> u32 {
-    let mut nums = AocDigit { inner: line };
-    let lhs = nums.next().unwrap_or(0);
-    let rhs = nums.last().unwrap_or(lhs);
-    let res = lhs * 10 + rhs;
-    println!("{line} -> {res}");
-    res
+fn process_line(line: &str, matcher: &Matcher) -> u32 {
+    matcher.report(line).value()
 }
 
-fn process(text: &str) -> u32 {
-    text.lines()
-        .filter(|l| l.is_ascii())
-        .map(|l| l.trim().to_lowercase())
-        .filter(|l| !l.is_empty())
-        .map(|l| process_line(&l))
-        .sum()
+fn process(text: &str, calibration: &Calibration) -> u32 {
+    let matcher = Matcher::new(calibration);
+    lines(text).map(|l| process_line(l, &matcher)).sum()
 }
 
 #[cfg(test)]
@@ -70,7 +606,7 @@ mod tests {
 
     #[test]
     fn test_sample_part1() {
-        let sum = process(SAMPLE);
+        let sum = process(SAMPLE, &Calibration::new(Mode::Digits));
         assert_eq!(sum, 142);
     }
 
@@ -86,13 +622,147 @@ mod tests {
 
     #[test]
     fn test_sample_part2() {
-        let sum = process(SAMPLE_2);
+        let sum = process(SAMPLE_2, &Calibration::new(Mode::Words));
         assert_eq!(sum, 281);
     }
 
     #[test]
     fn test_line() {
-        let res = process_line("jjhxddmg5mqxqbgfivextlcpnvtwothreetwonerzk");
+        let calibration = Calibration::new(Mode::Words);
+        let res = process_line(
+            "jjhxddmg5mqxqbgfivextlcpnvtwothreetwonerzk",
+            &Matcher::new(&calibration),
+        );
         assert_eq!(res, 51);
     }
+
+    #[test]
+    fn test_modes() {
+        let digits = Calibration::new(Mode::Digits);
+        let words = Calibration::new(Mode::Words);
+        assert_eq!(process_line("two1nine", &Matcher::new(&digits)), 11);
+        assert_eq!(process_line("two1nine", &Matcher::new(&words)), 29);
+        assert_eq!(process_line("eightwo", &Matcher::new(&digits)), 0);
+        assert_eq!(process(SAMPLE_2, &digits), 11 + 22 + 33 + 42 + 24 + 77);
+    }
+
+    #[test]
+    fn test_vocabularies() {
+        let custom = Vocabulary::parse("# comment\nklingon: pagh wa' cha' wej").unwrap();
+        let calibration = Calibration {
+            mode: Mode::Words,
+            vocabularies: vec![Vocabulary::builtin("spanish").unwrap(), custom[0].clone()],
+        };
+
+        let matcher = Matcher::new(&calibration);
+        let matches: Vec<_> = matcher.scan("uno7wej").map(|(_, m)| m).collect();
+        assert_eq!(
+            matches,
+            vec![
+                Match {
+                    value: 1,
+                    source: Source::Word("spanish")
+                },
+                Match {
+                    value: 7,
+                    source: Source::Digit
+                },
+                Match {
+                    value: 3,
+                    source: Source::Word("klingon")
+                },
+            ]
+        );
+        assert_eq!(process_line("xcincoxtwox", &matcher), 55);
+
+        assert!(Vocabulary::builtin("klingon").is_err());
+        assert!(Vocabulary::parse("no language here").is_err());
+    }
+
+    #[test]
+    fn test_matcher() {
+        let calibration = Calibration::new(Mode::Words);
+        let matcher = Matcher::new(&calibration);
+
+        let values: Vec<_> = matcher
+            .scan("eightwothree")
+            .map(|(span, m)| (span, m.value))
+            .collect();
+        assert_eq!(values, vec![(0..5, 8), (4..7, 2), (7..12, 3)]);
+
+        assert_eq!(matcher.first("xtwone3").map(|(_, m)| m.value), Some(2));
+        assert_eq!(matcher.last("3twone").map(|(_, m)| m.value), Some(1));
+        assert_eq!(matcher.last("no digits here"), None);
+        assert_eq!(process_line("TWO1NINE", &matcher), 29);
+    }
+
+    #[test]
+    fn test_compound() {
+        assert_eq!(spell_english(42), vec!["forty-two"]);
+        assert_eq!(
+            spell_english(105),
+            vec!["one hundred and five", "one hundred five"]
+        );
+
+        let calibration = Calibration::new(Mode::Compound);
+        let matcher = Matcher::new(&calibration);
+        assert_eq!(process_line("forty-two", &matcher), 42);
+        assert_eq!(process_line("xsixteenx", &matcher), 16);
+        assert_eq!(process_line("onehundred", &matcher), 10);
+        assert_eq!(process_line("3twentyone hundred and seven", &matcher), 37);
+        assert_eq!(process_line("seventy7eighty", &matcher), 70);
+        assert_eq!(process_line("twelve", &matcher), 12);
+        assert_eq!(process(SAMPLE_2, &calibration), 281);
+    }
+
+    #[test]
+    fn test_report() {
+        let calibration = Calibration::new(Mode::Words);
+        let matcher = Matcher::new(&calibration);
+
+        let report = matcher.report("xtwone3four");
+        assert_eq!(
+            report.first,
+            Some((
+                1..4,
+                Match {
+                    value: 2,
+                    source: Source::Word("english")
+                }
+            ))
+        );
+        assert_eq!(
+            report.last.as_ref().map(|(span, _)| span.clone()),
+            Some(7..11)
+        );
+        assert_eq!(report.render(), "x[two]ne3[four]");
+
+        assert_eq!(matcher.report("eightwo").render(), "[eightwo]");
+        assert_eq!(matcher.report("treb7uchet").render(), "treb[7]uchet");
+
+        let report = matcher.report("nothing");
+        assert!(!report.has_digit());
+        assert_eq!(report.to_string(), "  0  nothing  (no digit)");
+    }
+
+    #[test]
+    fn test_unicode() {
+        assert_eq!(decimal('7'), Some(7));
+        assert_eq!(decimal('٣'), Some(3));
+        assert_eq!(decimal('３'), Some(3));
+        assert_eq!(decimal('²'), None);
+        assert_eq!(decimal('a'), None);
+
+        let digits = Calibration::new(Mode::Digits);
+        assert_eq!(process("x٣y\n１é２\n", &digits), 33 + 12);
+
+        let calibration = Calibration {
+            mode: Mode::Words,
+            vocabularies: vec![Vocabulary::builtin("german").unwrap()],
+        };
+        let matcher = Matcher::new(&calibration);
+        let report = matcher.report("ÄFÜNFzwei٣");
+        assert_eq!(report.value(), 53);
+        assert_eq!(report.render(), "Ä[FÜNF]zwei[٣]");
+    }
 }
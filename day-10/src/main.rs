@@ -1,4 +1,5 @@
 use color_eyre::Result;
+use graph::Graph;
 use std::cmp::Ordering;
 use strum::EnumIter;
 use strum::IntoEnumIterator;
@@ -7,9 +8,20 @@ use Direction::*;
 const START_TILE_ID: TileID = 'S';
 
 fn main() -> Result<()> {
-    let text = std::fs::read_to_string("day-10/input.txt")?;
-    let res = part_1::process(&text);
-    println!("Part 1: {res}");
+    let config = aoc::Config::load("day-10")?;
+    let text = config.input()?;
+    let start = config.param_or("start", START_TILE_ID)?;
+
+    if config.repl() {
+        let tiles = parse::tiles(&text, start);
+        aoc::repl::run("day-10", repl::USAGE, |cmd, args| {
+            repl::eval(&tiles, cmd, args)
+        })?;
+        return Ok(());
+    }
+
+    let res = part_1::process(&text, start);
+    config.answer(1, res);
     Ok(())
 }
 
@@ -21,7 +33,7 @@ enum Direction {
     East,
 }
 
-#[derive(Debug, Eq, PartialEq)]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
 struct Location(usize, usize);
 
 type TileID = char;
@@ -30,20 +42,7 @@ type TileID = char;
 struct Tile(TileID, Location);
 
 #[derive(Debug)]
-struct TileMap(Vec<Vec<Tile>>);
-
-#[derive(Clone, Debug)]
-struct Trail<'a> {
-    map: &'a TileMap,
-    steps: Vec<&'a Tile>,
-    tile: &'a Tile,
-}
-
-#[derive(Debug)]
-struct Walker<'a> {
-    map: &'a TileMap,
-    trail: Trail<'a>,
-}
+struct TileMap(Vec<Vec<Tile>>, TileID);
 
 impl Location {
     fn mov(&self, d: Direction) -> Option<Location> {
@@ -72,23 +71,23 @@ impl Location {
 }
 
 impl Tile {
-    fn directions(&self) -> Vec<Direction> {
+    fn directions(&self, start: TileID) -> Vec<Direction> {
         let dirs: &[Direction] = match self.0 {
+            id if id == start => &[North, South, West, East],
             '|' => &[North, South],
             '-' => &[East, West],
             'L' => &[North, East],
             'J' => &[North, West],
             '7' => &[South, West],
             'F' => &[South, East],
-            'S' => &[North, South, West, East],
             _ => &[],
         };
         dirs.to_vec()
     }
 
-    fn connects(&self, other: &Tile) -> bool {
-        let lhs = self.directions();
-        let rhs = other.directions();
+    fn connects(&self, other: &Tile, start: TileID) -> bool {
+        let lhs = self.directions(start);
+        let rhs = other.directions(start);
 
         match self.1.cmp_row(&other.1) {
             Ordering::Less => lhs.contains(&South) && rhs.contains(&North),
@@ -104,113 +103,90 @@ impl Tile {
 
 impl TileMap {
     fn tile_at(&self, loc: Location) -> Option<&Tile> {
-        self.0.get(loc.0).map(|v| v.get(loc.1)).flatten()
+        self.0.get(loc.0).and_then(|v| v.get(loc.1))
     }
 
-    fn find(&self, id: TileID) -> Option<&Tile> {
-        self.0.iter().find_map(|r| r.iter().find(|t| t.0 == id))
+    fn start(&self) -> Option<&Tile> {
+        self.0.iter().find_map(|r| r.iter().find(|t| t.0 == self.1))
     }
 
     fn connections(&self, t: &Tile) -> Vec<&Tile> {
         Direction::iter()
             .flat_map(|d| t.1.mov(d))
             .flat_map(|l| self.tile_at(l))
-            .filter(|n| t.connects(n))
+            .filter(|n| t.connects(n, self.1))
             .collect()
     }
-}
 
-impl<'a> Trail<'a> {
-    fn new(map: &'a TileMap, start: &'a Tile) -> Trail<'a> {
-        Self {
-            map,
-            steps: Vec::new(),
-            tile: start,
+    /// Pipe network keyed by location; connected tiles get an edge each way.
+    fn graph(&self) -> Graph<Location> {
+        let mut graph = Graph::new();
+        for tile in self.0.iter().flatten() {
+            let from = graph.intern(tile.1);
+            for next in self.connections(tile) {
+                let to = graph.intern(next.1);
+                graph.add_edge(from, to, 1);
+            }
         }
-    }
-
-    fn reached(&self, t: &Tile) -> bool {
-        self.tile == t
-    }
-
-    fn count(&self) -> usize {
-        self.steps.len()
-    }
-
-    // TODO: could be consumed and skip cloning?
-    fn advance(&self) -> Vec<Trail<'a>> {
-        let mut steps = self.steps.clone();
-        steps.push(self.tile);
-        let cons = self.map.connections(self.tile);
-        cons.iter()
-            .filter(|t| !(self.steps.last() == Some(t)))
-            .map(|t| Self {
-                map: self.map,
-                steps: steps.clone(),
-                tile: t,
-            })
-            .collect()
+        graph
     }
 }
 
-impl<'a> Walker<'a> {
-    fn new(map: &'a TileMap, start: &'a Tile) -> Walker<'a> {
-        Self {
-            map,
-            trail: Trail::new(map, start),
-        }
-    }
-
-    fn reached(&self, t: &Tile) -> bool {
-        self.trail.reached(t) && self.trail.count() > 1
-    }
+mod parse {
+    use super::*;
 
-    fn advance(&self) -> Walker<'a> {
-        Self {
-            map: self.map,
-            trail: self.trail.advance().first().expect("trail").clone(),
-        }
+    pub fn tiles(s: &str, start: TileID) -> TileMap {
+        TileMap(
+            s.lines()
+                .map(|l| l.trim())
+                .filter(|l| !l.is_empty())
+                .enumerate()
+                .map(|(row, l)| {
+                    l.chars()
+                        .enumerate()
+                        .map(|(col, c)| Tile(c, Location(row, col)))
+                        .collect()
+                })
+                .collect(),
+            start,
+        )
     }
 }
 
-mod parse {
+mod part_1 {
     use super::*;
 
-    impl From<&str> for TileMap {
-        fn from(s: &str) -> Self {
-            TileMap(
-                s.lines()
-                    .map(|l| l.trim())
-                    .filter(|l| !l.is_empty())
-                    .enumerate()
-                    .map(|(row, l)| {
-                        l.chars()
-                            .enumerate()
-                            .map(|(col, c)| Tile(c, Location(row, col)))
-                            .collect()
-                    })
-                    .collect(),
-            )
-        }
+    pub fn process(s: &str, start: TileID) -> usize {
+        let tiles = parse::tiles(s, start);
+        let start = tiles.start().expect("Start tile");
+
+        let graph = tiles.graph();
+        let start = graph.id(&start.1).expect("Start node");
+        graph.bfs(start).map(|(_, depth)| depth).max().unwrap_or(0)
     }
 }
 
-mod part_1 {
+mod repl {
     use super::*;
+    use color_eyre::eyre::{bail, OptionExt};
+
+    pub const USAGE: &str = "tile <row> <col>    show a tile and the tiles it connects to
+start               show the start tile";
 
-    pub fn process(s: &str) -> usize {
-        let tiles = TileMap::from(s);
-        let start = tiles.find(START_TILE_ID).expect("Start tile 'S'");
+    fn show(tiles: &TileMap, tile: &Tile) -> String {
+        format!("{tile:?}\nconnections: {:?}", tiles.connections(tile))
+    }
 
-        let mut walker = Walker::new(&tiles, &start).advance();
-        loop {
-            walker = walker.advance();
-            if walker.reached(start) {
-                break;
+    pub fn eval(tiles: &TileMap, cmd: &str, args: &[&str]) -> Result<String> {
+        match (cmd, args) {
+            ("tile", [row, col]) => {
+                let loc = Location(row.parse()?, col.parse()?);
+                let tile = tiles.tile_at(loc).ok_or_eyre("no such tile")?;
+                Ok(show(tiles, tile))
             }
+            ("start", []) => Ok(show(tiles, tiles.start().ok_or_eyre("no start tile")?)),
+            _ => bail!("unknown command `{cmd}`, try `help`"),
         }
-
-        walker.trail.count() / 2
     }
 }
 
@@ -236,10 +212,10 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let res = part_1::process(SAMPLE);
+        let res = part_1::process(SAMPLE, START_TILE_ID);
         assert_eq!(res, 8);
 
-        let res = part_1::process(SAMPLE_2);
+        let res = part_1::process(SAMPLE_2, START_TILE_ID);
         assert_eq!(res, 4);
     }
 }
@@ -1,45 +1,121 @@
 use anyhow::{Context, Result};
-use std::cmp::max;
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt;
 use std::ops::Add;
 use std::str::FromStr;
 
 fn main() -> Result<()> {
-    let text = std::fs::read_to_string("day-2/input.txt")?;
-    let limit = Config {
-        red: 12,
-        green: 13,
-        blue: 14,
-    };
+    let settings = aoc::Config::load("day-2")?;
+    let text = settings.input()?;
+
+    if settings.repl() {
+        let games = parse::games(&text);
+        aoc::repl::run("day-2", repl::USAGE, |cmd, args| {
+            repl::eval(&games, cmd, args)
+        })?;
+        return Ok(());
+    }
+
+    let limits = BTreeMap::from([
+        ("red".to_string(), 12),
+        ("green".to_string(), 13),
+        ("blue".to_string(), 14),
+    ]);
+    let limit: Config = settings
+        .param_or("limits", limits)?
+        .into_iter()
+        .map(|(colour, count)| Ok((colour.parse()?, count)))
+        .collect::<Result<_>>()?;
+
+    if settings.param_or("lint", false)? {
+        for issue in lint::check(&text) {
+            println!("{issue}");
+        }
+    }
+    if settings.param_or("canonical", false)? {
+        for game in parse::games(&text) {
+            println!("{game}");
+        }
+    }
+
+    if settings.param_or("validate", false)? {
+        let games = parse::games(&text);
+        for game in &games {
+            let check = game.check(&limit);
+            match settings.format() {
+                aoc::Format::Text => println!("{check}"),
+                aoc::Format::Json => println!("{}", check.to_json()),
+            }
+        }
+    }
 
     let res = part_1::process(&text, limit);
-    println!("Part 1: {res}");
+    settings.answer(1, res);
 
     let res = part_2::process(&text);
-    println!("Part 2: {res}");
+    settings.answer(2, res);
+
+    let target = match (
+        settings.param::<f64>("fraction")?,
+        settings.param::<[u32; 2]>("games")?,
+    ) {
+        (Some(_), Some(_)) => anyhow::bail!("set either `fraction` or `games`, not both"),
+        (Some(f), None) => Some(optimise::Target::Fraction(f)),
+        (None, Some([first, last])) => Some(optimise::Target::Games(first..=last)),
+        (None, None) => None,
+    };
+    if let Some(target) = target {
+        let games = parse::games(&text);
+        let objective = match settings.param_or("minimise", "total".to_string())?.as_str() {
+            "total" => optimise::Objective::Total,
+            colour => optimise::Objective::Colour(colour.parse()?),
+        };
+        println!("{}", optimise::smallest_bag(&games, &target, &objective)?);
+    }
+
+    if let Some(query) = settings.param::<String>("query")? {
+        let games = parse::games(&text);
+        println!("{}", query::run(&query, &games)?);
+    }
+
+    if settings.param_or("infer", false)? {
+        let games = parse::games(&text);
+        let colours = palette(&games);
+        let cap = settings.param_or("max_cubes", 100)?;
+        for game in &games {
+            println!(
+                "Game {}: {}",
+                game.id,
+                infer::estimate(&game.set, &colours, cap)
+            );
+        }
+        let sets: Vec<DiceSet> = games.iter().flat_map(|g| g.set.clone()).collect();
+        println!("All games: {}", infer::estimate(&sets, &colours, cap));
+    }
 
     Ok(())
 }
 
-#[derive(Debug)]
-enum Dice {
-    Red,
-    Green,
-    Blue,
+/// A cube colour, lowercased.
+#[derive(Clone, Debug, Eq, Ord, PartialEq, PartialOrd)]
+struct Dice(String);
+
+impl fmt::Display for Dice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
 }
 
 #[derive(Debug)]
 struct DiceRoll(Dice, u32);
 
-#[derive(Default, Debug)]
-struct DiceSet {
-    red: u32,
-    green: u32,
-    blue: u32,
-}
+/// Cube counts by colour. A colour that is not in the map counts as zero.
+#[derive(Clone, Default, Debug, PartialEq)]
+struct DiceSet(BTreeMap<Dice, u32>);
 
 type Config = DiceSet;
 
-#[derive(Debug)]
+#[derive(Debug, PartialEq)]
 struct Game {
     id: u32,
     set: Vec<DiceSet>,
@@ -51,12 +127,12 @@ mod parse {
     impl FromStr for Dice {
         type Err = anyhow::Error;
         fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
-            match s.to_lowercase().as_str() {
-                "red" => Ok(Self::Red),
-                "green" => Ok(Self::Green),
-                "blue" => Ok(Self::Blue),
-                _ => Err(anyhow::anyhow!("No dice")),
-            }
+            let s = s.trim();
+            anyhow::ensure!(
+                !s.is_empty() && s.chars().all(char::is_alphabetic),
+                "invalid colour `{s}`"
+            );
+            Ok(Self(s.to_lowercase()))
         }
     }
 
@@ -70,13 +146,33 @@ mod parse {
         }
     }
 
+    /// The rolls of one set as written; a blank set has none.
+    pub fn rolls(s: &str) -> Result<Vec<DiceRoll>> {
+        if s.trim().is_empty() {
+            return Ok(Vec::new());
+        }
+        s.split(',').map(|s| s.parse()).collect()
+    }
+
+    /// A game line as written: its id and the rolls of each set, before rolls of the
+    /// same colour are added up.
+    pub fn line(s: &str) -> Result<(u32, Vec<Vec<DiceRoll>>)> {
+        let mut split = s.splitn(2, ':');
+        let id = split.next().context("missing game")?;
+        let set = split.next().context("missing set")?;
+
+        let id: u32 = id.split(' ').next_back().context("Missing ID")?.parse()?;
+        let sets = set.split(';').map(rolls).collect::<Result<_>>()?;
+        Ok((id, sets))
+    }
+
     impl FromStr for DiceSet {
         type Err = anyhow::Error;
 
         fn from_str(s: &str) -> Result<Self> {
-            let sets = s.split(',').map(|s| s.parse::<DiceRoll>()).flatten();
-            let sum = sets.fold(Self::default(), |acc, x| acc + x);
-            Ok(sum)
+            Ok(rolls(s)?
+                .into_iter()
+                .fold(Self::default(), |acc, x| acc + x))
         }
     }
 
@@ -84,16 +180,36 @@ mod parse {
         type Err = anyhow::Error;
 
         fn from_str(s: &str) -> Result<Self> {
-            let mut split = s.splitn(2, ':');
-            let id = split.next().context("missing game")?;
-            let set = split.next().context("missing set")?;
+            let (id, sets) = line(s)?;
+            let set = sets
+                .into_iter()
+                .map(|rolls| rolls.into_iter().fold(DiceSet::default(), |acc, x| acc + x))
+                .collect();
+            Ok(Self { id, set })
+        }
+    }
 
-            let id: u32 = id.split(' ').last().context("Missing ID")?.parse()?;
-            let set: Result<Vec<DiceSet>, Self::Err> =
-                set.split(';').map(|s| s.parse::<DiceSet>()).collect();
+    pub fn games(text: &str) -> Vec<Game> {
+        text.lines()
+            .map(|l| l.trim())
+            .filter(|l| !l.is_empty())
+            .flat_map(|l| l.parse::<Game>())
+            .collect()
+    }
+}
 
-            Ok(Self { id, set: set? })
-        }
+/// Writes a set as the puzzle does, with colours in alphabetical order.
+impl fmt::Display for DiceSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rolls: Vec<String> = self.0.iter().map(|(c, n)| format!("{n} {c}")).collect();
+        write!(f, "{}", rolls.join(", "))
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let sets: Vec<String> = self.set.iter().map(|s| s.to_string()).collect();
+        write!(f, "Game {}: {}", self.id, sets.join("; "))
     }
 }
 
@@ -102,22 +218,29 @@ impl Add<DiceRoll> for DiceSet {
 
     fn add(self, rhs: DiceRoll) -> Self::Output {
         let mut acc = self;
-        match rhs.0 {
-            Dice::Red => acc.red += rhs.1,
-            Dice::Green => acc.green += rhs.1,
-            Dice::Blue => acc.blue += rhs.1,
-        };
+        *acc.0.entry(rhs.0).or_default() += rhs.1;
         acc
     }
 }
 
+impl FromIterator<(Dice, u32)> for DiceSet {
+    fn from_iter<I: IntoIterator<Item = (Dice, u32)>>(iter: I) -> Self {
+        Self(iter.into_iter().collect())
+    }
+}
+
 impl DiceSet {
+    fn get(&self, colour: &Dice) -> u32 {
+        self.0.get(colour).copied().unwrap_or(0)
+    }
+
     fn contains(&self, other: &DiceSet) -> bool {
-        other.red <= self.red && other.green <= self.green && other.blue <= self.blue
+        other.0.iter().all(|(colour, n)| *n <= self.get(colour))
     }
 
-    fn power(&self) -> u32 {
-        self.red * self.green * self.blue
+    /// Product of the counts of `colours`, so a missing colour makes the power zero.
+    fn power<'a>(&self, colours: impl IntoIterator<Item = &'a Dice>) -> u32 {
+        colours.into_iter().map(|c| self.get(c)).product()
     }
 }
 
@@ -126,24 +249,67 @@ impl Game {
         self.set.iter().all(|s| limit.contains(s))
     }
 
+    fn check(&self, limit: &Config) -> validate::Check {
+        let violations: Vec<_> = self
+            .set
+            .iter()
+            .enumerate()
+            .flat_map(|(i, set)| {
+                set.0
+                    .iter()
+                    .filter(|(colour, n)| **n > limit.get(colour))
+                    .map(move |(colour, n)| validate::Violation {
+                        set: i + 1,
+                        colour: colour.clone(),
+                        count: *n,
+                        limit: limit.get(colour),
+                    })
+            })
+            .collect();
+
+        let status = if violations.is_empty() {
+            let min = self.min();
+            let headroom = limit
+                .0
+                .iter()
+                .map(|(colour, n)| (colour.clone(), n - min.get(colour)))
+                .collect();
+            validate::Status::Valid { headroom }
+        } else {
+            validate::Status::Invalid { violations }
+        };
+        validate::Check {
+            id: self.id,
+            status,
+        }
+    }
+
     fn min(&self) -> DiceSet {
-        self.set.iter().fold(DiceSet::default(), |acc, n| DiceSet {
-            red: max(n.red, acc.red),
-            green: max(n.green, acc.green),
-            blue: max(n.blue, acc.blue),
+        self.set.iter().fold(DiceSet::default(), |mut acc, n| {
+            for (colour, count) in &n.0 {
+                let max = acc.0.entry(colour.clone()).or_default();
+                *max = (*max).max(*count);
+            }
+            acc
         })
     }
+
+    fn colours(&self) -> impl Iterator<Item = &Dice> {
+        self.set.iter().flat_map(|s| s.0.keys())
+    }
+}
+
+/// Every colour mentioned anywhere in the games.
+fn palette(games: &[Game]) -> BTreeSet<Dice> {
+    games.iter().flat_map(|g| g.colours()).cloned().collect()
 }
 
 mod part_1 {
-    use crate::{Config, Game};
+    use crate::{parse, Config};
 
     pub fn process(text: &str, config: Config) -> u32 {
-        text.lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .map(|l| l.parse::<Game>())
-            .flatten()
+        parse::games(text)
+            .iter()
             .filter(|l| l.is_valid(&config))
             .map(|g| g.id)
             .sum()
@@ -151,17 +317,955 @@ mod part_1 {
 }
 
 mod part_2 {
-    use crate::Game;
+    use crate::{palette, parse};
 
     pub fn process(text: &str) -> u32 {
-        text.lines()
-            .map(|l| l.trim())
-            .filter(|l| !l.is_empty())
-            .map(|l| l.parse::<Game>())
-            .flatten()
-            .map(|g| g.min())
-            .map(|g| g.power())
-            .sum()
+        let games = parse::games(text);
+        let colours = palette(&games);
+        games.iter().map(|g| g.min().power(&colours)).sum()
+    }
+}
+
+/// Problems in the game log that parsing papers over.
+mod lint {
+    use super::*;
+
+    #[derive(Debug, PartialEq)]
+    pub enum Issue {
+        /// A line that does not parse, so it is left out of every answer.
+        Malformed {
+            line: usize,
+            error: String,
+        },
+        /// A set naming a colour more than once; its counts get added up.
+        Duplicate {
+            game: u32,
+            set: usize,
+            colour: Dice,
+        },
+        Empty {
+            game: u32,
+            set: usize,
+        },
+        /// Ids should count up from 1 without gaps.
+        OutOfSequence {
+            game: u32,
+            expected: u32,
+        },
+    }
+
+    impl fmt::Display for Issue {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Issue::Malformed { line, error } => write!(f, "line {line}: {error}"),
+                Issue::Duplicate { game, set, colour } => {
+                    write!(f, "game {game}, set {set}: {colour} appears more than once")
+                }
+                Issue::Empty { game, set } => write!(f, "game {game}, set {set}: empty set"),
+                Issue::OutOfSequence { game, expected } => {
+                    write!(f, "game {game}: expected id {expected}")
+                }
+            }
+        }
+    }
+
+    /// Every issue in `text`, in line order. Sets are numbered from 1.
+    pub fn check(text: &str) -> Vec<Issue> {
+        let mut issues = Vec::new();
+        let mut expected = 1;
+        let lines = text.lines().map(|l| l.trim()).enumerate();
+        for (i, l) in lines.filter(|(_, l)| !l.is_empty()) {
+            let (game, sets) = match parse::line(l) {
+                Ok(line) => line,
+                Err(e) => {
+                    issues.push(Issue::Malformed {
+                        line: i + 1,
+                        error: e.to_string(),
+                    });
+                    continue;
+                }
+            };
+
+            if game != expected {
+                issues.push(Issue::OutOfSequence { game, expected });
+            }
+            expected = game + 1;
+
+            for (set, rolls) in sets.iter().enumerate().map(|(i, r)| (i + 1, r)) {
+                if rolls.is_empty() {
+                    issues.push(Issue::Empty { game, set });
+                }
+                let (mut seen, mut flagged) = (BTreeSet::new(), BTreeSet::new());
+                for DiceRoll(colour, _) in rolls {
+                    if !seen.insert(colour) && flagged.insert(colour) {
+                        let colour = colour.clone();
+                        issues.push(Issue::Duplicate { game, set, colour });
+                    }
+                }
+            }
+        }
+        issues
+    }
+}
+
+/// Why each game is or is not possible with the configured bag.
+mod validate {
+    use super::*;
+
+    /// A colour in one set that needs more cubes than the bag holds.
+    #[derive(Debug, PartialEq)]
+    pub struct Violation {
+        /// 1-based position of the set within its game.
+        pub set: usize,
+        pub colour: Dice,
+        pub count: u32,
+        pub limit: u32,
+    }
+
+    impl Violation {
+        pub fn excess(&self) -> u32 {
+            self.count - self.limit
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Status {
+        /// Cubes left over per colour in the fullest draw of the game.
+        Valid {
+            headroom: DiceSet,
+        },
+        Invalid {
+            violations: Vec<Violation>,
+        },
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Check {
+        pub id: u32,
+        pub status: Status,
+    }
+
+    impl fmt::Display for Check {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let (status, detail): (_, Vec<String>) = match &self.status {
+                Status::Valid { headroom } => (
+                    "valid",
+                    headroom
+                        .0
+                        .iter()
+                        .map(|(c, n)| format!("{c} +{n}"))
+                        .collect(),
+                ),
+                Status::Invalid { violations } => (
+                    "invalid",
+                    violations
+                        .iter()
+                        .map(|v| {
+                            let (set, c, n, limit) = (v.set, &v.colour, v.count, v.limit);
+                            format!("set {set} {c} {n} > {limit} (+{})", v.excess())
+                        })
+                        .collect(),
+                ),
+            };
+            write!(f, "{:>4}  {status:<7}  {}", self.id, detail.join(", "))
+        }
+    }
+
+    impl Check {
+        pub fn to_json(&self) -> String {
+            match &self.status {
+                Status::Valid { headroom } => {
+                    let headroom: Vec<String> = headroom
+                        .0
+                        .iter()
+                        .map(|(c, n)| format!(r#""{c}":{n}"#))
+                        .collect();
+                    format!(
+                        r#"{{"game":{},"valid":true,"headroom":{{{}}}}}"#,
+                        self.id,
+                        headroom.join(",")
+                    )
+                }
+                Status::Invalid { violations } => {
+                    let violations: Vec<String> = violations
+                        .iter()
+                        .map(|v| {
+                            format!(
+                                r#"{{"set":{},"colour":"{}","count":{},"limit":{},"excess":{}}}"#,
+                                v.set,
+                                v.colour,
+                                v.count,
+                                v.limit,
+                                v.excess()
+                            )
+                        })
+                        .collect();
+                    format!(
+                        r#"{{"game":{},"valid":false,"violations":[{}]}}"#,
+                        self.id,
+                        violations.join(",")
+                    )
+                }
+            }
+        }
+    }
+}
+
+/// Smallest bags that keep a chosen share of the games possible.
+mod optimise {
+    use super::*;
+    use std::ops::RangeInclusive;
+
+    /// What "smallest" means: fewest cubes overall, or fewest of one colour with the
+    /// total breaking ties.
+    pub enum Objective {
+        Total,
+        Colour(Dice),
+    }
+
+    pub enum Target {
+        /// At least this share of all games, between 0 and 1.
+        Fraction(f64),
+        /// Every game whose id is in the range.
+        Games(RangeInclusive<u32>),
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub struct Plan {
+        pub bag: DiceSet,
+        /// Ids of every game the bag makes possible, targeted or not.
+        pub admitted: Vec<u32>,
+        pub games: usize,
+    }
+
+    impl fmt::Display for Plan {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let colours: Vec<String> = self.bag.0.iter().map(|(c, n)| format!("{c} {n}")).collect();
+            let total: u32 = self.bag.0.values().sum();
+            write!(
+                f,
+                "Bag: {} ({total} cubes) admits {} of {} games",
+                colours.join(", "),
+                self.admitted.len(),
+                self.games
+            )
+        }
+    }
+
+    struct Search<'a> {
+        colours: Vec<&'a Dice>,
+        mins: Vec<Vec<u32>>,
+        need: usize,
+        objective: Option<usize>,
+        best: Option<((u32, u32), Vec<u32>)>,
+    }
+
+    impl Search<'_> {
+        fn cost(&self, bag: &[u32]) -> (u32, u32) {
+            let total = bag.iter().sum();
+            (self.objective.map_or(total, |i| bag[i]), total)
+        }
+
+        /// Fixes one colour at a time to each count some game needs; the last colour
+        /// then takes the smallest count that still admits enough games.
+        fn run(&mut self, bag: &mut Vec<u32>, admitted: &[usize]) {
+            if admitted.len() < self.need {
+                return;
+            }
+            let colour = bag.len();
+            let mut counts: Vec<u32> = admitted.iter().map(|g| self.mins[*g][colour]).collect();
+            counts.sort_unstable();
+
+            if colour + 1 == self.colours.len() {
+                bag.push(counts[self.need - 1]);
+                let cost = self.cost(bag);
+                if self.best.as_ref().is_none_or(|(best, _)| cost < *best) {
+                    self.best = Some((cost, bag.clone()));
+                }
+                bag.pop();
+                return;
+            }
+
+            counts.dedup();
+            for n in counts {
+                bag.push(n);
+                // Colours still to fix cost at least nothing, which bounds the branch.
+                let mut floor = bag.clone();
+                floor.resize(self.colours.len(), 0);
+                let floor = self.cost(&floor);
+                if self.best.as_ref().is_none_or(|(best, _)| floor < *best) {
+                    let next: Vec<usize> = admitted
+                        .iter()
+                        .copied()
+                        .filter(|g| self.mins[*g][colour] <= n)
+                        .collect();
+                    self.run(bag, &next);
+                }
+                bag.pop();
+            }
+        }
+    }
+
+    /// The fewest of `total` games that make up at least `fraction` of them. Shares
+    /// within a rounding error of `fraction` count as reaching it, so 0.55 of 100
+    /// games needs 55 rather than 56.
+    pub fn games_needed(fraction: f64, total: usize) -> usize {
+        let reaches = |k: usize| k as f64 / total as f64 >= fraction * (1.0 - f64::EPSILON);
+        if total == 0 {
+            return 0;
+        }
+        let mut need = ((fraction * total as f64).ceil() as usize).min(total);
+        while need > 0 && reaches(need - 1) {
+            need -= 1;
+        }
+        while need < total && !reaches(need) {
+            need += 1;
+        }
+        need
+    }
+
+    /// The cheapest bag under `objective` that meets `target`. Fails when the
+    /// target names no games at all, or the objective a colour no game has.
+    pub fn smallest_bag(games: &[Game], target: &Target, objective: &Objective) -> Result<Plan> {
+        let palette = palette(games);
+        if let Objective::Colour(c) = objective {
+            anyhow::ensure!(palette.contains(c), "unknown colour `{c}`");
+        }
+
+        let bag = match target {
+            Target::Games(ids) => {
+                let chosen: Vec<&Game> = games.iter().filter(|g| ids.contains(&g.id)).collect();
+                anyhow::ensure!(!chosen.is_empty(), "no games with ids {ids:?}");
+                chosen.iter().fold(DiceSet::default(), |mut acc, g| {
+                    for (c, n) in g.min().0 {
+                        let max = acc.0.entry(c).or_default();
+                        *max = (*max).max(n);
+                    }
+                    acc
+                })
+            }
+            Target::Fraction(fraction) => {
+                let need = games_needed(fraction.clamp(0.0, 1.0), games.len());
+                let colours: Vec<&Dice> = palette.iter().collect();
+                if need == 0 || colours.is_empty() {
+                    DiceSet::default()
+                } else {
+                    let objective = match objective {
+                        Objective::Total => None,
+                        Objective::Colour(c) => colours.iter().position(|p| *p == c),
+                    };
+                    let mins = games
+                        .iter()
+                        .map(|g| {
+                            let min = g.min();
+                            colours.iter().map(|c| min.get(c)).collect()
+                        })
+                        .collect();
+                    let mut search = Search {
+                        colours,
+                        mins,
+                        need,
+                        objective,
+                        best: None,
+                    };
+                    let all: Vec<usize> = (0..games.len()).collect();
+                    search.run(&mut Vec::new(), &all);
+                    let (_, bag) = search.best.context("no bag admits the target games")?;
+                    search
+                        .colours
+                        .iter()
+                        .map(|c| (*c).clone())
+                        .zip(bag)
+                        .collect()
+                }
+            }
+        };
+
+        let admitted = games
+            .iter()
+            .filter(|g| g.is_valid(&bag))
+            .map(|g| g.id)
+            .collect();
+        Ok(Plan {
+            bag,
+            admitted,
+            games: games.len(),
+        })
+    }
+}
+
+/// Ad-hoc questions over the game log.
+///
+/// A query is an optional filter followed by an optional `| aggregate`:
+///
+/// ```text
+/// any(blue > 10)                 ids of games where some set has more than 10 blue
+/// power > 1000 | count           how many games have a minimal bag power over 1000
+/// id <= 50 | sets avg(red)       average red per set across games 1 to 50
+/// ```
+///
+/// Per game, `id`, `sets`, `power` and `total` are available, and a colour name is
+/// the most cubes of that colour drawn at once. `any`, `all`, `sum`, `avg`, `min`
+/// and `max` evaluate their argument over the game's sets, where a colour name is
+/// the count in that set and `total` the cubes drawn. Aggregates are `ids` (the
+/// default), `count`, or `sum`, `avg`, `min` or `max` of a per-game expression;
+/// with `sets` in front they run over every set of the matching games instead.
+mod query {
+    use super::*;
+    use anyhow::{bail, ensure};
+
+    #[derive(Clone, Debug, PartialEq)]
+    enum Token {
+        Num(f64),
+        Name(String),
+        Op(&'static str),
+    }
+
+    const OPS: [&str; 14] = [
+        "<=", ">=", "==", "!=", "<", ">", "+", "-", "*", "/", "(", ")", "|", "=",
+    ];
+
+    fn tokens(s: &str) -> Result<Vec<Token>> {
+        let mut tokens = Vec::new();
+        let mut rest = s.trim_start();
+        while !rest.is_empty() {
+            let len = if let Some(op) = OPS.iter().find(|op| rest.starts_with(**op)) {
+                tokens.push(Token::Op(if *op == "=" { "==" } else { op }));
+                op.len()
+            } else if rest.starts_with(|c: char| c.is_ascii_digit() || c == '.') {
+                let len = rest
+                    .find(|c: char| !c.is_ascii_digit() && c != '.')
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Num(rest[..len].parse()?));
+                len
+            } else if rest.starts_with(char::is_alphabetic) {
+                let len = rest
+                    .find(|c: char| !c.is_alphanumeric() && c != '_')
+                    .unwrap_or(rest.len());
+                tokens.push(Token::Name(rest[..len].to_lowercase()));
+                len
+            } else {
+                bail!("unexpected `{}`", rest.chars().next().unwrap_or_default());
+            };
+            rest = rest[len..].trim_start();
+        }
+        Ok(tokens)
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Expr {
+        Num(f64),
+        Var(String),
+        Call(String, Box<Expr>),
+        Not(Box<Expr>),
+        Binary(&'static str, Box<Expr>, Box<Expr>),
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Aggregate {
+        Ids,
+        Count,
+        Fold(String, Expr),
+    }
+
+    #[derive(Debug, PartialEq)]
+    struct Query {
+        filter: Option<Expr>,
+        per_set: bool,
+        aggregate: Aggregate,
+    }
+
+    const FOLDS: [&str; 4] = ["sum", "avg", "min", "max"];
+
+    struct Parser {
+        tokens: Vec<Token>,
+        at: usize,
+    }
+
+    impl Parser {
+        fn peek(&self) -> Option<&Token> {
+            self.tokens.get(self.at)
+        }
+
+        fn eat(&mut self, op: &str) -> bool {
+            let found = matches!(self.peek(), Some(Token::Op(o)) if *o == op);
+            self.at += found as usize;
+            found
+        }
+
+        fn eat_name(&mut self, name: &str) -> bool {
+            let found = matches!(self.peek(), Some(Token::Name(n)) if n == name);
+            self.at += found as usize;
+            found
+        }
+
+        fn expect(&mut self, op: &str) -> Result<()> {
+            ensure!(self.eat(op), "expected `{op}`");
+            Ok(())
+        }
+
+        fn query(&mut self) -> Result<Query> {
+            let filter = match self.peek() {
+                None | Some(Token::Op("|")) => None,
+                _ => Some(self.or()?),
+            };
+            let (per_set, aggregate) = if self.eat("|") {
+                let per_set = self.eat_name("sets");
+                (per_set, self.aggregate()?)
+            } else {
+                (false, Aggregate::Ids)
+            };
+            ensure!(self.peek().is_none(), "unexpected {:?}", self.peek());
+            Ok(Query {
+                filter,
+                per_set,
+                aggregate,
+            })
+        }
+
+        fn aggregate(&mut self) -> Result<Aggregate> {
+            match self.tokens.get(self.at).cloned() {
+                Some(Token::Name(n)) if n == "ids" => {
+                    self.at += 1;
+                    Ok(Aggregate::Ids)
+                }
+                Some(Token::Name(n)) if n == "count" => {
+                    self.at += 1;
+                    Ok(Aggregate::Count)
+                }
+                Some(Token::Name(n)) if FOLDS.contains(&n.as_str()) => {
+                    self.at += 1;
+                    self.expect("(")?;
+                    let expr = self.or()?;
+                    self.expect(")")?;
+                    Ok(Aggregate::Fold(n, expr))
+                }
+                t => bail!("expected an aggregate, found {t:?}"),
+            }
+        }
+
+        fn binary(
+            &mut self,
+            ops: &[&'static str],
+            next: fn(&mut Self) -> Result<Expr>,
+        ) -> Result<Expr> {
+            let mut lhs = next(self)?;
+            while let Some(op) = ops.iter().find(|op| match self.peek() {
+                Some(Token::Name(n)) => n == *op,
+                Some(Token::Op(o)) => o == *op,
+                _ => false,
+            }) {
+                self.at += 1;
+                lhs = Expr::Binary(op, Box::new(lhs), Box::new(next(self)?));
+            }
+            Ok(lhs)
+        }
+
+        fn or(&mut self) -> Result<Expr> {
+            self.binary(&["or"], Self::and)
+        }
+
+        fn and(&mut self) -> Result<Expr> {
+            self.binary(&["and"], Self::not)
+        }
+
+        fn not(&mut self) -> Result<Expr> {
+            if self.eat_name("not") {
+                return Ok(Expr::Not(Box::new(self.not()?)));
+            }
+            let lhs = self.sum()?;
+            match self.peek() {
+                Some(Token::Op(op)) if ["<", "<=", ">", ">=", "==", "!="].contains(op) => {
+                    let op = *op;
+                    self.at += 1;
+                    Ok(Expr::Binary(op, Box::new(lhs), Box::new(self.sum()?)))
+                }
+                _ => Ok(lhs),
+            }
+        }
+
+        fn sum(&mut self) -> Result<Expr> {
+            self.binary(&["+", "-"], Self::product)
+        }
+
+        fn product(&mut self) -> Result<Expr> {
+            self.binary(&["*", "/"], Self::atom)
+        }
+
+        fn atom(&mut self) -> Result<Expr> {
+            let token = self.peek().cloned().context("unexpected end of query")?;
+            self.at += 1;
+            match token {
+                Token::Num(n) => Ok(Expr::Num(n)),
+                Token::Op("(") => {
+                    let expr = self.or()?;
+                    self.expect(")")?;
+                    Ok(expr)
+                }
+                Token::Name(name) if self.eat("(") => {
+                    let arg = self.or()?;
+                    self.expect(")")?;
+                    Ok(Expr::Call(name, Box::new(arg)))
+                }
+                Token::Name(name) => Ok(Expr::Var(name)),
+                Token::Op(op) => bail!("unexpected `{op}`"),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Value {
+        Num(f64),
+        Bool(bool),
+    }
+
+    impl Value {
+        fn num(self) -> Result<f64> {
+            match self {
+                Value::Num(n) => Ok(n),
+                Value::Bool(_) => bail!("expected a number, found a condition"),
+            }
+        }
+
+        fn bool(self) -> Result<bool> {
+            match self {
+                Value::Bool(b) => Ok(b),
+                Value::Num(_) => bail!("expected a condition, found a number"),
+            }
+        }
+    }
+
+    enum Scope<'a> {
+        Game(&'a Game),
+        Set(&'a DiceSet),
+    }
+
+    struct Eval<'a> {
+        colours: &'a BTreeSet<Dice>,
+    }
+
+    impl Eval<'_> {
+        fn colour(&self, name: &str) -> Result<Dice> {
+            let colour = Dice(name.to_string());
+            ensure!(self.colours.contains(&colour), "unknown name `{name}`");
+            Ok(colour)
+        }
+
+        fn fold(name: &str, values: &[f64]) -> Result<f64> {
+            if name == "sum" {
+                return Ok(values.iter().sum());
+            }
+            ensure!(!values.is_empty(), "`{name}` of nothing");
+            Ok(match name {
+                "avg" => values.iter().sum::<f64>() / values.len() as f64,
+                "min" => values.iter().copied().fold(f64::INFINITY, f64::min),
+                _ => values.iter().copied().fold(f64::NEG_INFINITY, f64::max),
+            })
+        }
+
+        fn eval(&self, expr: &Expr, scope: &Scope) -> Result<Value> {
+            Ok(match (expr, scope) {
+                (Expr::Num(n), _) => Value::Num(*n),
+                (Expr::Var(name), Scope::Game(game)) => Value::Num(match name.as_str() {
+                    "id" => game.id as f64,
+                    "sets" => game.set.len() as f64,
+                    "power" => game.min().power(self.colours) as f64,
+                    "total" => game.min().0.values().sum::<u32>() as f64,
+                    _ => game.min().get(&self.colour(name)?) as f64,
+                }),
+                (Expr::Var(name), Scope::Set(set)) => Value::Num(match name.as_str() {
+                    "total" => set.0.values().sum::<u32>() as f64,
+                    _ => set.get(&self.colour(name)?) as f64,
+                }),
+                (Expr::Call(name, arg), Scope::Game(game)) => {
+                    let values: Vec<Value> = game
+                        .set
+                        .iter()
+                        .map(|s| self.eval(arg, &Scope::Set(s)))
+                        .collect::<Result<_>>()?;
+                    match name.as_str() {
+                        "any" | "all" => {
+                            let flags: Vec<bool> =
+                                values.iter().map(|v| v.bool()).collect::<Result<_>>()?;
+                            Value::Bool(match name.as_str() {
+                                "any" => flags.contains(&true),
+                                _ => !flags.contains(&false),
+                            })
+                        }
+                        _ if FOLDS.contains(&name.as_str()) => {
+                            let values: Vec<f64> =
+                                values.iter().map(|v| v.num()).collect::<Result<_>>()?;
+                            Value::Num(Self::fold(name, &values)?)
+                        }
+                        _ => bail!("unknown function `{name}`"),
+                    }
+                }
+                (Expr::Call(name, _), Scope::Set(_)) => bail!("`{name}` only applies to a game"),
+                (Expr::Not(e), _) => Value::Bool(!self.eval(e, scope)?.bool()?),
+                (Expr::Binary(op, lhs, rhs), _) => {
+                    let lhs = self.eval(lhs, scope)?;
+                    let rhs = self.eval(rhs, scope)?;
+                    match *op {
+                        "and" => Value::Bool(lhs.bool()? && rhs.bool()?),
+                        "or" => Value::Bool(lhs.bool()? || rhs.bool()?),
+                        _ => {
+                            let (l, r) = (lhs.num()?, rhs.num()?);
+                            match *op {
+                                "+" => Value::Num(l + r),
+                                "-" => Value::Num(l - r),
+                                "*" => Value::Num(l * r),
+                                "/" => Value::Num(l / r),
+                                "<" => Value::Bool(l < r),
+                                "<=" => Value::Bool(l <= r),
+                                ">" => Value::Bool(l > r),
+                                ">=" => Value::Bool(l >= r),
+                                "==" => Value::Bool(l == r),
+                                _ => Value::Bool(l != r),
+                            }
+                        }
+                    }
+                }
+            })
+        }
+    }
+
+    #[derive(Debug, PartialEq)]
+    pub enum Answer {
+        Ids(Vec<u32>),
+        Value(f64),
+    }
+
+    impl fmt::Display for Answer {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                Answer::Ids(ids) => {
+                    let ids: Vec<String> = ids.iter().map(|id| id.to_string()).collect();
+                    write!(f, "{}", ids.join(" "))
+                }
+                Answer::Value(v) if v.fract() == 0.0 => write!(f, "{v}"),
+                Answer::Value(v) => write!(f, "{v:.3}"),
+            }
+        }
+    }
+
+    pub fn run(query: &str, games: &[Game]) -> Result<Answer> {
+        let mut parser = Parser {
+            tokens: tokens(query)?,
+            at: 0,
+        };
+        let query = parser.query()?;
+        let colours = palette(games);
+        let eval = Eval { colours: &colours };
+
+        let mut matching = Vec::new();
+        for game in games {
+            let keep = match &query.filter {
+                Some(filter) => eval.eval(filter, &Scope::Game(game))?.bool()?,
+                None => true,
+            };
+            if keep {
+                matching.push(game);
+            }
+        }
+
+        let scopes: Vec<Scope> = match query.per_set {
+            true => matching
+                .iter()
+                .flat_map(|g| g.set.iter().map(Scope::Set))
+                .collect(),
+            false => matching.iter().map(|g| Scope::Game(g)).collect(),
+        };
+        Ok(match query.aggregate {
+            Aggregate::Ids => {
+                ensure!(!query.per_set, "`ids` does not apply to sets");
+                Answer::Ids(matching.iter().map(|g| g.id).collect())
+            }
+            Aggregate::Count => Answer::Value(scopes.len() as f64),
+            Aggregate::Fold(name, expr) => {
+                let values: Vec<f64> = scopes
+                    .iter()
+                    .map(|s| eval.eval(&expr, s)?.num())
+                    .collect::<Result<_>>()?;
+                Answer::Value(Eval::fold(&name, &values)?)
+            }
+        })
+    }
+}
+
+/// Maximum-likelihood bag contents.
+///
+/// Each set is taken to be a draw without replacement from the same bag, with the
+/// cubes put back between sets, so a set has a multivariate hypergeometric
+/// likelihood. The search climbs from the smallest feasible bag one cube at a time.
+mod infer {
+    use super::*;
+    use std::ops::RangeInclusive;
+
+    /// Log-likelihood drop bounding a 95% likelihood interval (half of chi-square(1)).
+    const INTERVAL: f64 = 1.92;
+
+    #[derive(Debug)]
+    pub struct Estimate {
+        pub bag: DiceSet,
+        pub log_likelihood: f64,
+        /// Per colour, the counts whose likelihood stays within `INTERVAL` of the best
+        /// bag with every other colour held at its estimate.
+        pub intervals: BTreeMap<Dice, RangeInclusive<u32>>,
+        /// Largest count searched. An estimate or interval reaching it means the
+        /// games cannot tell the bag apart from an even bigger one.
+        pub cap: u32,
+    }
+
+    impl fmt::Display for Estimate {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            let colours: Vec<String> = self
+                .intervals
+                .iter()
+                .map(|(c, range)| {
+                    let n = self.bag.get(c);
+                    let n = if n == self.cap {
+                        format!("{n}+")
+                    } else {
+                        n.to_string()
+                    };
+                    match *range.end() == self.cap {
+                        true => format!("{c} {n} ({}..)", range.start()),
+                        false => format!("{c} {n} ({range:?})"),
+                    }
+                })
+                .collect();
+            write!(
+                f,
+                "{}  [ln L = {:.2}]",
+                colours.join(", "),
+                self.log_likelihood
+            )
+        }
+    }
+
+    struct Model<'a> {
+        sets: &'a [DiceSet],
+        colours: Vec<&'a Dice>,
+        ln_factorial: Vec<f64>,
+    }
+
+    impl Model<'_> {
+        fn ln_choose(&self, n: u32, k: u32) -> f64 {
+            let f = &self.ln_factorial;
+            f[n as usize] - f[k as usize] - f[(n - k) as usize]
+        }
+
+        fn log_likelihood(&self, bag: &[u32]) -> f64 {
+            let total = bag.iter().sum();
+            self.sets
+                .iter()
+                .map(|set| {
+                    let drawn = set.0.values().sum();
+                    let ways: f64 = self
+                        .colours
+                        .iter()
+                        .zip(bag)
+                        .map(|(c, n)| self.ln_choose(*n, set.get(c)))
+                        .sum();
+                    ways - self.ln_choose(total, drawn)
+                })
+                .sum()
+        }
+    }
+
+    /// Estimates the bag behind `sets` over `colours`, with at most `cap` cubes of a
+    /// colour. Ties keep the smaller bag.
+    pub fn estimate(sets: &[DiceSet], colours: &BTreeSet<Dice>, cap: u32) -> Estimate {
+        let min = sets.iter().fold(DiceSet::default(), |mut acc, s| {
+            for (c, n) in &s.0 {
+                let max = acc.0.entry(c.clone()).or_default();
+                *max = (*max).max(*n);
+            }
+            acc
+        });
+        let colours: Vec<&Dice> = colours.iter().collect();
+        let floor: Vec<u32> = colours.iter().map(|c| min.get(c)).collect();
+        let cap = floor.iter().fold(cap, |cap, n| cap.max(*n));
+
+        let mut ln_factorial = vec![0.0];
+        for i in 1..=cap as usize * colours.len().max(1) {
+            ln_factorial.push(ln_factorial[i - 1] + (i as f64).ln());
+        }
+        let model = Model {
+            sets,
+            colours,
+            ln_factorial,
+        };
+
+        let mut bag = floor.clone();
+        let mut best = model.log_likelihood(&bag);
+        loop {
+            let mut step = None;
+            for i in 0..bag.len() {
+                for n in [bag[i] + 1, bag[i].saturating_sub(1)] {
+                    if n < floor[i] || n > cap || n == bag[i] {
+                        continue;
+                    }
+                    let mut next = bag.clone();
+                    next[i] = n;
+                    let ll = model.log_likelihood(&next);
+                    if ll > step.as_ref().map_or(best, |(_, l)| *l) + f64::EPSILON {
+                        step = Some((next, ll));
+                    }
+                }
+            }
+            match step {
+                Some((next, ll)) => (bag, best) = (next, ll),
+                None => break,
+            }
+        }
+
+        let within = |i: usize, n: u32| {
+            let mut probe = bag.clone();
+            probe[i] = n;
+            model.log_likelihood(&probe) >= best - INTERVAL
+        };
+        let intervals = (0..bag.len())
+            .map(|i| {
+                let lo = (floor[i]..bag[i]).find(|n| within(i, *n)).unwrap_or(bag[i]);
+                let hi = (bag[i] + 1..=cap)
+                    .take_while(|n| within(i, *n))
+                    .last()
+                    .unwrap_or(bag[i]);
+                (model.colours[i].clone(), lo..=hi)
+            })
+            .collect();
+
+        Estimate {
+            bag: model
+                .colours
+                .iter()
+                .map(|c| (*c).clone())
+                .zip(bag)
+                .collect(),
+            log_likelihood: best,
+            intervals,
+            cap,
+        }
+    }
+}
+
+mod repl {
+    use super::*;
+
+    pub const USAGE: &str = "game <id>        show a parsed game and its minimal bag
+query <query>    filter and aggregate games, e.g. `query power > 1000 | count`";
+
+    pub fn eval(games: &[Game], cmd: &str, args: &[&str]) -> Result<String> {
+        match (cmd, args) {
+            ("game", [id]) => {
+                let id: u32 = id.parse()?;
+                let game = games.iter().find(|g| g.id == id).context("no such game")?;
+                Ok(format!("{game:#?}\nmin: {:?}", game.min()))
+            }
+            ("query", [_, ..]) => Ok(query::run(&args.join(" "), games)?.to_string()),
+            _ => anyhow::bail!("unknown command `{cmd}`, try `help`"),
+        }
     }
 }
 
@@ -177,13 +1281,16 @@ mod tests {
     Game 5: 6 red, 1 blue, 3 green; 2 blue, 1 red, 2 green
     "#;
 
+    fn bag(counts: &[(&str, u32)]) -> DiceSet {
+        counts
+            .iter()
+            .map(|(colour, n)| (Dice(colour.to_string()), *n))
+            .collect()
+    }
+
     #[test]
     fn test_part_1() {
-        let cfg = Config {
-            red: 12,
-            green: 13,
-            blue: 14,
-        };
+        let cfg = bag(&[("red", 12), ("green", 13), ("blue", 14)]);
         let res = part_1::process(SAMPLE_1, cfg);
         assert_eq!(res, 8)
     }
@@ -193,4 +1300,152 @@ mod tests {
         let res = part_2::process(SAMPLE_1);
         assert_eq!(res, 2286)
     }
+
+    #[test]
+    fn test_colours() {
+        let text = "Game 1: 2 Purple, 1 red; 3 red\nGame 2: 1 red, 4 blue\n";
+        let games = parse::games(text);
+        assert_eq!(games[0].min(), bag(&[("purple", 2), ("red", 3)]));
+
+        let colours = palette(&games);
+        assert_eq!(colours.len(), 3);
+        assert_eq!(games[0].min().power(&colours), 0);
+        assert_eq!(part_2::process(text), 0);
+
+        assert_eq!(part_1::process(text, bag(&[("red", 3), ("purple", 2)])), 1);
+        assert_eq!(part_1::process(text, bag(&[("red", 3), ("blue", 4)])), 2);
+        assert!("3 r3d".parse::<DiceRoll>().is_err());
+    }
+
+    #[test]
+    fn test_infer() {
+        let games = parse::games("Game 1: 1 red, 1 blue; 1 blue, 1 red; 1 red, 1 blue");
+        let colours = palette(&games);
+        let estimate = infer::estimate(&games[0].set, &colours, 100);
+        assert_eq!(estimate.bag, bag(&[("red", 1), ("blue", 1)]));
+        assert_eq!(estimate.log_likelihood, 0.0);
+        assert_eq!(estimate.intervals[&Dice("red".to_string())], 1..=2);
+
+        let games = parse::games(SAMPLE_1);
+        let colours = palette(&games);
+        for game in &games {
+            let estimate = infer::estimate(&game.set, &colours, 100);
+            assert!(game.set.iter().all(|s| estimate.bag.contains(s)));
+        }
+    }
+
+    #[test]
+    fn test_check() {
+        let games = parse::games(SAMPLE_1);
+        let limit = bag(&[("red", 12), ("green", 13), ("blue", 14)]);
+
+        let check = games[0].check(&limit);
+        assert_eq!(
+            check.to_string(),
+            "   1  valid    blue +8, green +11, red +8"
+        );
+        assert_eq!(
+            check.to_json(),
+            r#"{"game":1,"valid":true,"headroom":{"blue":8,"green":11,"red":8}}"#
+        );
+
+        let check = games[2].check(&limit);
+        assert_eq!(check.to_string(), "   3  invalid  set 1 red 20 > 12 (+8)");
+        assert_eq!(
+            check.to_json(),
+            r#"{"game":3,"valid":false,"violations":[{"set":1,"colour":"red","count":20,"limit":12,"excess":8}]}"#
+        );
+
+        let checks: Vec<_> = games.iter().map(|g| g.check(&limit)).collect();
+        let valid = checks
+            .iter()
+            .filter(|c| matches!(c.status, validate::Status::Valid { .. }));
+        assert_eq!(valid.map(|c| c.id).sum::<u32>(), 8);
+    }
+
+    #[test]
+    fn test_optimise() {
+        use optimise::{games_needed, smallest_bag, Objective, Target};
+
+        let games = parse::games(SAMPLE_1);
+        let plan = smallest_bag(&games, &Target::Fraction(0.6), &Objective::Total).unwrap();
+        assert_eq!(plan.bag, bag(&[("red", 6), ("green", 3), ("blue", 6)]));
+        assert_eq!(plan.admitted, vec![1, 2, 5]);
+
+        let plan = smallest_bag(&games, &Target::Fraction(1.0), &Objective::Total).unwrap();
+        assert_eq!(plan.bag, bag(&[("red", 20), ("green", 13), ("blue", 15)]));
+
+        let red = Objective::Colour(Dice("red".to_string()));
+        let plan = smallest_bag(&games, &Target::Fraction(0.4), &red).unwrap();
+        assert_eq!(plan.bag, bag(&[("red", 4), ("green", 3), ("blue", 6)]));
+
+        let plan = smallest_bag(&games, &Target::Games(1..=2), &red).unwrap();
+        assert_eq!(
+            plan.to_string(),
+            "Bag: blue 6, green 3, red 4 (13 cubes) admits 2 of 5 games"
+        );
+        assert!(smallest_bag(&games, &Target::Games(8..=9), &red).is_err());
+        let purple = Objective::Colour(Dice("purple".to_string()));
+        let err = smallest_bag(&games, &Target::Fraction(0.6), &purple).unwrap_err();
+        assert_eq!(err.to_string(), "unknown colour `purple`");
+
+        let text: String = (1..=100)
+            .map(|id| format!("Game {id}: {id} red\n"))
+            .collect();
+        let games = parse::games(&text);
+        for (fraction, red) in [(0.55, 55), (0.07, 7), (0.555, 56), (0.29, 29)] {
+            let plan =
+                smallest_bag(&games, &Target::Fraction(fraction), &Objective::Total).unwrap();
+            assert_eq!(plan.bag, bag(&[("red", red)]));
+        }
+
+        assert_eq!(games_needed(0.3, 10_000_000), 3_000_000);
+        assert_eq!(games_needed(0.3, 10_000_001), 3_000_001);
+        assert_eq!(games_needed(1.0 / 3.0, 3_000_000_000), 1_000_000_000);
+        assert_eq!(games_needed(0.0, 5), 0);
+        assert_eq!(games_needed(1.0, 5), 5);
+    }
+
+    #[test]
+    fn test_query() {
+        let games = parse::games(SAMPLE_1);
+        let run = |q: &str| query::run(q, &games).unwrap().to_string();
+
+        assert_eq!(run("any(blue > 10)"), "4");
+        assert_eq!(run("all(red <= 12) and not id = 2"), "1 5");
+        assert_eq!(run("power > 100 | count"), "2");
+        assert_eq!(run("| sum(power)"), "2286");
+        assert_eq!(run("id <= 2 | sets avg(red)"), "1");
+        assert_eq!(run("| max(avg(total))"), "20.667");
+        assert_eq!(run("red > 12"), "3 4");
+
+        assert!(query::run("purple > 1", &games).is_err());
+        assert!(query::run("id > | count", &games).is_err());
+        assert!(query::run("| sets ids", &games).is_err());
+    }
+
+    #[test]
+    fn test_serialize() {
+        let games = parse::games(SAMPLE_1);
+        assert_eq!(
+            games[0].to_string(),
+            "Game 1: 3 blue, 4 red; 6 blue, 2 green, 1 red; 2 green"
+        );
+        for game in &games {
+            assert_eq!(&game.to_string().parse::<Game>().unwrap(), game);
+        }
+
+        let text = "Game 1: 3 red, 2 red; ; 1 blue\nGame 3: 1 red\nGame 4 2 red\n";
+        let issues: Vec<String> = lint::check(text).iter().map(|i| i.to_string()).collect();
+        assert_eq!(
+            issues,
+            vec![
+                "game 1, set 1: red appears more than once",
+                "game 1, set 2: empty set",
+                "game 3: expected id 2",
+                "line 3: missing set",
+            ]
+        );
+        assert_eq!(parse::games(text)[0].to_string(), "Game 1: 5 red; ; 1 blue");
+    }
 }
@@ -1,64 +1,209 @@
+use anyhow::{Context, Result};
+use core::iter;
 use core::ops::Range;
+use graph::Graph;
+use std::fmt;
+use std::str::FromStr;
 
-fn main() {
-    let text = std::fs::read_to_string("day-3/input.txt").unwrap();
-    let r1 = part_1::process(&text);
-    let r2 = part_2::process(&text);
-    println!("Part 1: {r1}");
-    println!("Part 2: {r2}");
+fn main() -> Result<()> {
+    let config = aoc::Config::load("day-3")?;
+    let text = config.input()?;
+
+    let defaults = Rules::default();
+    let rules = Rules {
+        symbols: config.param("symbols")?,
+        gears: config.param_or("gears", defaults.gears)?,
+        count: match config.param::<usize>("gear_numbers")? {
+            Some(0) => anyhow::bail!("gear_numbers must be at least 1"),
+            Some(k) if config.param_or("at_least", false)? => Count::AtLeast(k),
+            Some(k) => Count::Exactly(k),
+            None => defaults.count,
+        },
+        combine: match config.param::<String>("ratio")? {
+            Some(c) => c.parse()?,
+            None => defaults.combine,
+        },
+        adjacency: Adjacency {
+            shape: match config.param::<String>("adjacency")? {
+                Some(s) => s.parse()?,
+                None => defaults.adjacency.shape,
+            },
+            radius: config.param_or("radius", defaults.adjacency.radius)?,
+            wrap: config.param_or("wrap", defaults.adjacency.wrap)?,
+        },
+    };
+
+    if let Some(style) = config.param::<String>("render")? {
+        let style = style.parse()?;
+        print!("{}", render::render(&parse(&text, &rules), &rules, style));
+    }
+
+    if let Some(report) = config.param::<String>("report")? {
+        let report = report.parse()?;
+        print!("{}", report::report(&parse(&text, &rules), report));
+    }
+
+    let r1 = part_1::process(&text, &rules);
+    let r2 = part_2::process(&text, &rules)?;
+    config.answer(1, r1);
+    config.answer(2, r2);
+    Ok(())
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Copy, Clone, Debug, PartialEq)]
 struct Loc {
     x: usize,
     y: usize,
 }
 
-impl Loc {
-    fn new(y: usize) -> Self {
-        Self { x: 0, y }
-    }
-}
-
 #[derive(Clone, Copy, Debug)]
 struct Element<Inner: Clone + Copy> {
     val: Inner,
     loc: Loc,
+    /// Columns taken in the text, so "007" is 3 wide.
+    width: usize,
 }
 
 type Num = Element<u32>;
 type Symbol = Element<char>;
 
-impl Symbol {
-    fn is_gear(&self) -> bool {
-        self.val == '*'
+/// How many numbers a gear must touch, at least one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Count {
+    Exactly(usize),
+    AtLeast(usize),
+}
+
+/// How the numbers around a gear make its ratio.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Combine {
+    Product,
+    Sum,
+}
+
+/// Which cells around a number count as touching it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Shape {
+    /// Cells within `radius` steps up, down, left or right (Manhattan distance).
+    Orthogonal,
+    /// Cells within `radius` steps in any direction, diagonals included (Chebyshev
+    /// distance).
+    Diagonal,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Adjacency {
+    shape: Shape,
+    radius: usize,
+    /// Whether the schematic wraps around, so the last row and column touch the first.
+    wrap: bool,
+}
+
+impl Adjacency {
+    fn reaches(&self, dy: usize, dx: usize) -> bool {
+        match self.shape {
+            Shape::Orthogonal => dy + dx <= self.radius,
+            Shape::Diagonal => dy.max(dx) <= self.radius,
+        }
     }
 }
 
-impl Num {
-    fn digits(&self) -> usize {
-        let mut divisor = 1;
-        let mut count = 1;
-        while self.val >= divisor * 10 {
-            divisor *= 10;
-            count += 1;
+impl FromStr for Shape {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "orthogonal" => Ok(Self::Orthogonal),
+            "diagonal" => Ok(Self::Diagonal),
+            _ => anyhow::bail!("unknown adjacency `{s}`, expected `orthogonal` or `diagonal`"),
+        }
+    }
+}
+
+/// What the schematic's characters mean. The default is the puzzle's: any char
+/// other than a digit or '.' is a symbol, and a '*' next to exactly two numbers is
+/// a gear whose ratio is their product, with numbers touching the eight cells
+/// around them.
+#[derive(Clone, Debug)]
+struct Rules {
+    /// Chars that count as symbols, or `None` for every char but digits and '.'.
+    /// Chars that are not symbols read as empty cells.
+    symbols: Option<String>,
+    gears: String,
+    count: Count,
+    combine: Combine,
+    adjacency: Adjacency,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Self {
+            symbols: None,
+            gears: "*".to_string(),
+            count: Count::Exactly(2),
+            combine: Combine::Product,
+            adjacency: Adjacency {
+                shape: Shape::Diagonal,
+                radius: 1,
+                wrap: false,
+            },
         }
-        count
     }
+}
+
+impl Rules {
+    fn is_symbol(&self, c: char) -> bool {
+        match &self.symbols {
+            Some(symbols) => symbols.contains(c),
+            None => c != '.',
+        }
+    }
+
+    /// Whether a gear touching `n` numbers has the right number of them.
+    fn fits(&self, n: usize) -> bool {
+        match self.count {
+            Count::Exactly(k) => n == k,
+            Count::AtLeast(k) => n >= k,
+        }
+    }
+
+    /// The ratio of a gear touching `nums`, or an error if it does not fit in a
+    /// `u64`.
+    fn ratio(&self, nums: &[u32]) -> Result<u64> {
+        let mut wide = nums.iter().map(|n| u64::from(*n));
+        match self.combine {
+            Combine::Product => wide.try_fold(1u64, |a, n| a.checked_mul(n)),
+            Combine::Sum => wide.try_fold(0u64, |a, n| a.checked_add(n)),
+        }
+        .with_context(|| format!("gear ratio of {nums:?} overflows u64"))
+    }
+}
 
-    fn range(&self) -> Range<usize> {
-        let mut range = self.loc.x..self.loc.x + self.digits();
-        if range.start > 0 {
-            range.start -= 1;
+impl FromStr for Combine {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "product" => Ok(Self::Product),
+            "sum" => Ok(Self::Sum),
+            _ => anyhow::bail!("unknown ratio `{s}`, expected `product` or `sum`"),
         }
-        range.end += 1;
-        range
     }
+}
 
-    fn is_adyacent(&self, sym: &Symbol) -> bool {
-        let range_x = self.range();
-        let dy = self.loc.y.abs_diff(sym.loc.y);
-        dy <= 1 && range_x.contains(&sym.loc.x)
+impl Symbol {
+    fn is_gear(&self, rules: &Rules) -> bool {
+        rules.gears.contains(self.val)
+    }
+}
+
+impl Num {
+    fn digits(&self) -> usize {
+        self.width
+    }
+
+    fn span(&self) -> Range<usize> {
+        self.loc.x..self.loc.x + self.digits()
     }
 }
 
@@ -82,86 +227,501 @@ impl Item {
             _ => None,
         }
     }
+
+    fn loc(&self) -> Loc {
+        match self {
+            Self::Num(n) => n.loc,
+            Self::Symbol(s) => s.loc,
+        }
+    }
+
+    /// Columns the item covers in its row.
+    fn span(&self) -> Range<usize> {
+        match self {
+            Self::Num(n) => n.loc.x..n.loc.x + n.width,
+            Self::Symbol(s) => s.loc.x..s.loc.x + s.width,
+        }
+    }
 }
 
-fn parse_line(y: usize, line: &str) -> Vec<Item> {
+/// Parsed items indexed by row, so adjacency checks only look at nearby cells.
+///
+/// `parse` yields items row by row and left to right, so each row is a contiguous,
+/// column-ordered run of `items`.
+#[derive(Debug)]
+struct Schematic {
+    items: Vec<Item>,
+    rows: Vec<Range<usize>>,
+    width: usize,
+    adjacency: Adjacency,
+}
+
+impl Schematic {
+    fn new(items: Vec<Item>, width: usize, height: usize, adjacency: Adjacency) -> Self {
+        let mut rows: Vec<Range<usize>> = Vec::with_capacity(height);
+        for (i, item) in items.iter().enumerate() {
+            let y = item.loc().y;
+            while rows.len() <= y {
+                rows.push(i..i);
+            }
+            rows[y].end = i + 1;
+        }
+        rows.resize(height.max(rows.len()), items.len()..items.len());
+        Self {
+            items,
+            rows,
+            width,
+            adjacency,
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    /// Rows within the adjacency radius of `y`, each once.
+    fn rows_near(&self, y: usize) -> Vec<usize> {
+        let (r, h) = (self.adjacency.radius, self.height());
+        let mut rows: Vec<usize> = match self.adjacency.wrap {
+            true if 2 * r + 1 >= h => (0..h).collect(),
+            true => (y + h - r..=y + h + r).map(|y| y % h).collect(),
+            false => (y.saturating_sub(r)..(y + r + 1).min(h)).collect(),
+        };
+        rows.sort_unstable();
+        rows.dedup();
+        rows
+    }
+
+    /// Column ranges within the adjacency radius of `xs`; the second one is empty
+    /// unless they wrap around the edge.
+    fn cols_near(&self, xs: Range<usize>) -> [Range<usize>; 2] {
+        let (r, w) = (self.adjacency.radius, self.width);
+        if !self.adjacency.wrap {
+            return [xs.start.saturating_sub(r)..xs.end + r, 0..0];
+        }
+        let len = xs.len() + 2 * r;
+        if len >= w {
+            return [0..w, 0..0];
+        }
+        let start = (xs.start + w - r) % w;
+        match start + len <= w {
+            true => [start..start + len, 0..0],
+            false => [start..w, 0..start + len - w],
+        }
+    }
+
+    /// Indices of items close enough to cells `xs` of row `y` that they may be
+    /// adjacent.
+    fn near(&self, y: usize, xs: Range<usize>) -> Vec<usize> {
+        let mut found: Vec<usize> = Vec::new();
+        for y in self.rows_near(y) {
+            let row = self.rows[y].clone();
+            for cols in self.cols_near(xs.clone()) {
+                let items = &self.items[row.clone()];
+                let first = row.start + items.partition_point(|i| i.span().end <= cols.start);
+                let end = row.start + items.partition_point(|i| i.span().start < cols.end);
+                found.extend(first..end.max(first));
+            }
+        }
+        found.sort_unstable();
+        found.dedup();
+        found
+    }
+
+    /// Rows and columns between a cell and the nearest cell of a number, going round
+    /// the edge when the schematic wraps.
+    fn distance(&self, n: &Num, to: Loc) -> (usize, usize) {
+        let (first, last) = (n.loc.x, n.loc.x + n.digits() - 1);
+        let dy = n.loc.y.abs_diff(to.y);
+        if !self.adjacency.wrap {
+            return (
+                dy,
+                first.saturating_sub(to.x).max(to.x.saturating_sub(last)),
+            );
+        }
+
+        let (h, w) = (self.height(), self.width);
+        let dx = match n.span().contains(&to.x) {
+            true => 0,
+            false => ((first + w - to.x) % w).min((to.x + w - last) % w),
+        };
+        (dy.min(h - dy), dx)
+    }
+
+    fn adjacent(&self, n: &Num, s: &Symbol) -> bool {
+        let (dy, dx) = self.distance(n, s.loc);
+        self.adjacency.reaches(dy, dx)
+    }
+
+    fn nums(&self) -> impl Iterator<Item = Num> + '_ {
+        self.items.iter().filter_map(|i| i.num())
+    }
+
+    fn syms(&self) -> impl Iterator<Item = Symbol> + '_ {
+        self.items.iter().filter_map(|i| i.sym())
+    }
+
+    /// Indices of the items touching `item`: the symbols around a number, or the
+    /// numbers around a symbol.
+    fn touching(&self, item: &Item) -> Vec<usize> {
+        self.near(item.loc().y, item.span())
+            .into_iter()
+            .filter(|i| match (item, &self.items[*i]) {
+                (Item::Num(n), Item::Symbol(s)) | (Item::Symbol(s), Item::Num(n)) => {
+                    self.adjacent(n, s)
+                }
+                _ => false,
+            })
+            .collect()
+    }
+
+    /// The items touching `items[i]`, in schematic order.
+    fn neighbours(&self, i: usize) -> impl Iterator<Item = &Item> + '_ {
+        self.touching(&self.items[i])
+            .into_iter()
+            .map(|i| &self.items[i])
+    }
+
+    fn syms_near(&self, n: Num) -> impl Iterator<Item = Symbol> + '_ {
+        self.touching(&Item::Num(n))
+            .into_iter()
+            .filter_map(|i| self.items[i].sym())
+    }
+
+    fn nums_near(&self, s: Symbol) -> impl Iterator<Item = Num> + '_ {
+        self.touching(&Item::Symbol(s))
+            .into_iter()
+            .filter_map(|i| self.items[i].num())
+    }
+
+    fn is_part(&self, n: Num) -> bool {
+        self.syms_near(n).next().is_some()
+    }
+
+    /// The numbers around `s` if it is a gear with the right number of them.
+    fn gear_nums(&self, s: Symbol, rules: &Rules) -> Option<Vec<u32>> {
+        if !s.is_gear(rules) {
+            return None;
+        }
+        let nums: Vec<u32> = self.nums_near(s).map(|n| n.val).collect();
+        rules.fits(nums.len()).then_some(nums)
+    }
+
+    /// The ratio of `s` if it is a gear with the right number of neighbours.
+    fn gear_ratio(&self, s: Symbol, rules: &Rules) -> Result<Option<u64>> {
+        self.gear_nums(s, rules)
+            .map(|nums| rules.ratio(&nums))
+            .transpose()
+            .with_context(|| format!("gear at {},{}", s.loc.y, s.loc.x))
+    }
+
+    /// Adjacency between items, keyed by their index; touching items get an edge
+    /// each way.
+    fn graph(&self) -> Graph<usize> {
+        let mut graph = Graph::new();
+        for i in 0..self.items.len() {
+            let from = graph.intern(i);
+            for next in self.touching(&self.items[i]) {
+                let to = graph.intern(next);
+                graph.add_edge(from, to, 1);
+            }
+        }
+        graph
+    }
+
+    /// Items grouped by chains of adjacency, in the order of their first item. A
+    /// number touching no symbol is a cluster on its own, as is such a symbol.
+    fn clusters(&self) -> Vec<Cluster> {
+        let graph = self.graph();
+        graph
+            .components()
+            .into_iter()
+            .map(|ids| {
+                let mut cluster = Cluster::default();
+                for id in ids {
+                    match &self.items[*graph.node(id)] {
+                        Item::Num(n) => cluster.nums.push(*n),
+                        Item::Symbol(s) => cluster.syms.push(*s),
+                    }
+                }
+                cluster
+            })
+            .collect()
+    }
+}
+
+/// Numbers and symbols joined through shared neighbours.
+#[derive(Debug, Default)]
+struct Cluster {
+    nums: Vec<Num>,
+    syms: Vec<Symbol>,
+}
+
+impl Cluster {
+    fn sum(&self) -> u32 {
+        self.nums.iter().map(|n| n.val).sum()
+    }
+}
+
+impl fmt::Display for Cluster {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if !self.syms.is_empty() {
+            let syms: String = self.syms.iter().map(|s| s.val).collect();
+            write!(f, "{syms}: ")?;
+        }
+        match self.nums.is_empty() {
+            true => write!(f, "0"),
+            false => {
+                let nums: Vec<String> = self.nums.iter().map(|n| n.val.to_string()).collect();
+                write!(f, "{} = {}", nums.join(" + "), self.sum())
+            }
+        }
+    }
+}
+
+fn parse_line(y: usize, line: &str, rules: &Rules) -> Vec<Item> {
     let mut coll = Vec::new();
-    let mut num = 0;
-    let mut loc = Loc::new(y);
+    // The number being read, as its start column and value so far.
+    let mut num: Option<(usize, u32)> = None;
+    let number = |(start, val): (usize, u32), end: usize| {
+        let loc = Loc { x: start, y };
+        Item::Num(Num {
+            val,
+            loc,
+            width: end - start,
+        })
+    };
 
+    let mut width = 0;
     for (x, c) in line.chars().enumerate() {
+        width = x + 1;
         if let Some(d) = c.to_digit(10) {
-            if num == 0 {
-                loc.x = x;
-            }
-            num *= 10;
-            num += d;
+            let (_, val) = num.get_or_insert((x, 0));
+            *val = *val * 10 + d;
             continue;
         }
 
-        if num != 0 {
-            let item = Item::Num(Num { val: num, loc });
-            coll.push(item);
-            loc = Loc::new(y);
-            num = 0;
+        if let Some(n) = num.take() {
+            coll.push(number(n, x));
         }
 
-        if c != '.' {
-            loc.x = x;
-            let item = Item::Symbol(Symbol { val: c, loc });
-            coll.push(item);
-            loc = Loc::new(y);
+        if rules.is_symbol(c) {
+            let loc = Loc { x, y };
+            coll.push(Item::Symbol(Symbol {
+                val: c,
+                loc,
+                width: 1,
+            }));
         }
     }
 
-    if num != 0 {
-        let item = Item::Num(Num { val: num, loc });
-        coll.push(item);
+    // A number running up to the edge.
+    if let Some(n) = num {
+        coll.push(number(n, width));
     }
 
     coll
 }
 
-fn parse(text: &str) -> Vec<Item> {
-    text.lines()
+fn parse(text: &str, rules: &Rules) -> Schematic {
+    let lines: Vec<&str> = text
+        .lines()
         .map(|l| l.trim())
         .filter(|l| !l.is_empty())
+        .collect();
+    let width = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let items = lines
+        .iter()
         .enumerate()
-        .flat_map(|(i, l)| parse_line(i, l))
-        .collect()
+        .flat_map(|(i, l)| parse_line(i, l, rules))
+        .collect();
+    Schematic::new(items, width, lines.len(), rules.adjacency)
 }
 
 mod part_1 {
-    use crate::parse;
+    use crate::{parse, Rules};
 
-    pub fn process(text: &str) -> u32 {
-        let items = parse(text);
-        let syms = items.iter().filter_map(|e| e.sym());
-        let nums = items.iter().filter_map(|e| e.num());
-        nums.filter(|n| syms.clone().any(|s| n.is_adyacent(&s)))
+    pub fn process(text: &str, rules: &Rules) -> u32 {
+        let schematic = parse(text, rules);
+        schematic
+            .nums()
+            .filter(|n| schematic.is_part(*n))
             .map(|n| n.val)
             .sum()
     }
 }
 
 mod part_2 {
-    use crate::parse;
-
-    pub fn process(text: &str) -> u32 {
-        let items = parse(text);
-        let nums = items.iter().filter_map(|e| e.num());
-        let gears = items.iter().filter_map(|e| e.sym()).filter(|s| s.is_gear());
-
-        gears
-            .map(|g| {
-                nums.clone()
-                    .filter(|n| n.is_adyacent(&g))
-                    .map(|n| n.val)
-                    .collect::<Vec<_>>()
-            })
-            .filter(|v| v.len() == 2)
-            .map(|v| v.iter().copied().reduce(|l, r| l * r).unwrap_or(0))
-            .sum()
+    use crate::{parse, Rules};
+    use anyhow::{Context, Result};
+
+    pub fn process(text: &str, rules: &Rules) -> Result<u64> {
+        let schematic = parse(text, rules);
+        let mut sum: u64 = 0;
+        for s in schematic.syms() {
+            if let Some(ratio) = schematic.gear_ratio(s, rules)? {
+                sum = sum
+                    .checked_add(ratio)
+                    .context("sum of gear ratios overflows u64")?;
+            }
+        }
+        Ok(sum)
+    }
+}
+
+/// Lists what each item touches, or how the items cluster together.
+mod report {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Report {
+        /// Each item followed by the items it touches, skipping lone items.
+        Neighbours,
+        /// Each cluster holding a symbol, with the sum of its numbers.
+        Clusters,
+    }
+
+    impl FromStr for Report {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "neighbours" => Ok(Self::Neighbours),
+                "clusters" => Ok(Self::Clusters),
+                _ => anyhow::bail!("unknown report `{s}`, expected `neighbours` or `clusters`"),
+            }
+        }
+    }
+
+    fn name(item: &Item) -> String {
+        match item {
+            Item::Num(n) => format!("{:0width$}", n.val, width = n.width),
+            Item::Symbol(s) => s.val.to_string(),
+        }
+    }
+
+    pub fn report(schematic: &Schematic, report: Report) -> String {
+        let mut out = String::new();
+        match report {
+            Report::Neighbours => {
+                for (i, item) in schematic.items.iter().enumerate() {
+                    let near: Vec<String> = schematic.neighbours(i).map(name).collect();
+                    if !near.is_empty() {
+                        let Loc { x, y } = item.loc();
+                        out.push_str(&format!("{} at {y},{x}: {}\n", name(item), near.join(" ")));
+                    }
+                }
+            }
+            Report::Clusters => {
+                for cluster in schematic.clusters() {
+                    if !cluster.syms.is_empty() {
+                        out.push_str(&format!("{cluster}\n"));
+                    }
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Draws the schematic back out, showing how each item was classified.
+mod render {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    pub enum Style {
+        /// Part numbers green, other numbers red, symbols cyan and gears yellow.
+        Ansi,
+        /// Each row followed by a row of markers: `p` under part numbers, `n` under
+        /// other numbers, `s` under symbols and `g` under gears.
+        Plain,
+    }
+
+    impl FromStr for Style {
+        type Err = anyhow::Error;
+
+        fn from_str(s: &str) -> Result<Self, Self::Err> {
+            match s {
+                "ansi" => Ok(Self::Ansi),
+                "plain" => Ok(Self::Plain),
+                _ => anyhow::bail!("unknown render style `{s}`, expected `ansi` or `plain`"),
+            }
+        }
+    }
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    enum Kind {
+        Part,
+        Loose,
+        Symbol,
+        Gear,
+    }
+
+    impl Kind {
+        fn ansi(&self) -> &str {
+            match self {
+                Kind::Part => "32",
+                Kind::Loose => "31",
+                Kind::Symbol => "36",
+                Kind::Gear => "1;33",
+            }
+        }
+
+        fn marker(&self) -> char {
+            match self {
+                Kind::Part => 'p',
+                Kind::Loose => 'n',
+                Kind::Symbol => 's',
+                Kind::Gear => 'g',
+            }
+        }
+    }
+
+    /// Non-symbol chars come out as '.', since the items do not record them.
+    pub fn render(schematic: &Schematic, rules: &Rules, style: Style) -> String {
+        let mut out = String::new();
+        for row in &schematic.rows {
+            let (mut line, mut marks) = (String::new(), String::new());
+            let mut x = 0;
+            for item in &schematic.items[row.clone()] {
+                let (text, kind) = match item {
+                    Item::Num(n) => {
+                        let kind = if schematic.is_part(*n) {
+                            Kind::Part
+                        } else {
+                            Kind::Loose
+                        };
+                        (format!("{:0width$}", n.val, width = n.width), kind)
+                    }
+                    Item::Symbol(s) => {
+                        let kind = match schematic.gear_nums(*s, rules) {
+                            Some(_) => Kind::Gear,
+                            None => Kind::Symbol,
+                        };
+                        (s.val.to_string(), kind)
+                    }
+                };
+
+                let gap = item.span().start - x;
+                line.push_str(&".".repeat(gap));
+                marks.push_str(&" ".repeat(gap));
+                match style {
+                    Style::Ansi => line.push_str(&format!("\x1b[{}m{text}\x1b[0m", kind.ansi())),
+                    Style::Plain => line.push_str(&text),
+                }
+                marks.extend(iter::repeat_n(kind.marker(), item.span().len()));
+                x = item.span().end;
+            }
+            line.push_str(&".".repeat(schematic.width.saturating_sub(x)));
+
+            out.push_str(&line);
+            out.push('\n');
+            if style == Style::Plain {
+                out.push_str(marks.trim_end());
+                out.push('\n');
+            }
+        }
+        out
     }
 }
 
@@ -184,13 +744,207 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let sum = part_1::process(SAMPLE_1);
+        let sum = part_1::process(SAMPLE_1, &Rules::default());
         assert_eq!(sum, 4361);
     }
 
     #[test]
     fn test_part_2() {
-        let sum = part_2::process(SAMPLE_1);
+        let sum = part_2::process(SAMPLE_1, &Rules::default()).unwrap();
         assert_eq!(sum, 467 * 35 + 755 * 598);
     }
+
+    #[test]
+    fn test_index() {
+        // A large generated schematic, checked against the all-pairs definition.
+        let text: String = (0..120)
+            .map(|y| {
+                (0..120)
+                    .map(|x| match (x * 7 + y * 13) % 11 {
+                        0 => '*',
+                        1 => '#',
+                        2..=5 => char::from(b'1' + ((x + y) % 9) as u8),
+                        _ => '.',
+                    })
+                    .collect::<String>()
+                    + "\n"
+            })
+            .collect();
+
+        let wrapped = Rules {
+            adjacency: Adjacency {
+                shape: Shape::Orthogonal,
+                radius: 2,
+                wrap: true,
+            },
+            ..Rules::default()
+        };
+        for rules in [Rules::default(), wrapped] {
+            let schematic = parse(&text, &rules);
+            let syms: Vec<_> = schematic.syms().collect();
+            let expected: u32 = schematic
+                .nums()
+                .filter(|n| syms.iter().any(|s| schematic.adjacent(n, s)))
+                .map(|n| n.val)
+                .sum();
+            assert_eq!(part_1::process(&text, &rules), expected);
+
+            for s in syms.iter().step_by(37) {
+                let near: Vec<_> = schematic.nums_near(*s).map(|n| n.loc).collect();
+                let all: Vec<_> = schematic
+                    .nums()
+                    .filter(|n| schematic.adjacent(n, s))
+                    .map(|n| n.loc)
+                    .collect();
+                assert_eq!(near, all);
+            }
+        }
+    }
+
+    #[test]
+    fn test_zeros() {
+        let text = "0*.007\n....#.\n.00...";
+        let schematic = parse(text, &Rules::default());
+        let nums: Vec<_> = schematic
+            .nums()
+            .map(|n| (n.val, n.loc.x, n.loc.y, n.width))
+            .collect();
+        assert_eq!(nums, vec![(0, 0, 0, 1), (7, 3, 0, 3), (0, 1, 2, 2)]);
+        assert_eq!(part_1::process("...007\n..#...", &Rules::default()), 7);
+        assert_eq!(part_1::process("007...\n....#.", &Rules::default()), 0);
+        assert_eq!(part_2::process("0*5\n...", &Rules::default()).unwrap(), 0);
+
+        // '.' as a symbol only counts where the text has one, not past a row's end.
+        let rules = Rules {
+            symbols: Some(".*".to_string()),
+            ..Rules::default()
+        };
+        assert_eq!(part_1::process("12", &rules), 0);
+        assert_eq!(part_1::process("12.", &rules), 12);
+        let plain = render::render(&parse("1.\n..", &rules), &rules, render::Style::Plain);
+        assert_eq!(plain, "1.\nps\n..\nss\n");
+    }
+
+    #[test]
+    fn test_rules() {
+        let rules = Rules {
+            symbols: Some("*$".to_string()),
+            gears: "*$".to_string(),
+            count: Count::AtLeast(1),
+            combine: "sum".parse().unwrap(),
+            ..Rules::default()
+        };
+        assert_eq!(part_1::process(SAMPLE_1, &rules), 4361 - 633 - 592);
+        assert_eq!(
+            part_2::process(SAMPLE_1, &rules).unwrap(),
+            467 + 35 + 617 + 664 + 755 + 598
+        );
+
+        let rules = Rules {
+            count: Count::Exactly(1),
+            ..Rules::default()
+        };
+        assert_eq!(part_2::process(SAMPLE_1, &rules).unwrap(), 617);
+        assert!("max".parse::<Combine>().is_err());
+
+        // Four 3-digit numbers multiply past `u32::MAX`.
+        let rules = Rules {
+            count: Count::AtLeast(2),
+            ..Rules::default()
+        };
+        let text = "999.999\n...*...\n999.999";
+        assert_eq!(part_2::process(text, &rules).unwrap(), 999u64.pow(4));
+
+        // Eight of them overflow even a `u64`, which is an error rather than a panic.
+        let rules = Rules {
+            adjacency: Adjacency {
+                radius: 2,
+                ..Rules::default().adjacency
+            },
+            ..rules
+        };
+        let text = "999.999\n999.999\n...*...\n999.999\n999.999";
+        let err = part_2::process(text, &rules).unwrap_err();
+        assert_eq!(err.to_string(), "gear at 2,3");
+        assert!(format!("{err:#}").ends_with("overflows u64"));
+    }
+
+    #[test]
+    fn test_adjacency() {
+        let rules = |shape, radius, wrap| Rules {
+            adjacency: Adjacency {
+                shape,
+                radius,
+                wrap,
+            },
+            ..Rules::default()
+        };
+
+        let text = "12...\n..*..\n.....\n...3.\n";
+        assert_eq!(part_1::process(text, &rules(Shape::Diagonal, 1, false)), 12);
+        assert_eq!(
+            part_1::process(text, &rules(Shape::Orthogonal, 1, false)),
+            0
+        );
+        assert_eq!(
+            part_1::process(text, &rules(Shape::Orthogonal, 2, false)),
+            12
+        );
+        assert_eq!(part_1::process(text, &rules(Shape::Diagonal, 2, false)), 15);
+
+        let text = "1...#\n.....\n.....\n...2.\n";
+        assert_eq!(part_1::process(text, &rules(Shape::Diagonal, 1, false)), 0);
+        assert_eq!(part_1::process(text, &rules(Shape::Diagonal, 1, true)), 3);
+        assert_eq!(part_1::process(text, &rules(Shape::Orthogonal, 1, true)), 1);
+    }
+
+    #[test]
+    fn test_render() {
+        let rules = Rules::default();
+        let schematic = parse(SAMPLE_1, &rules);
+        let plain = render::render(&schematic, &rules, render::Style::Plain);
+        let lines: Vec<&str> = plain.lines().collect();
+        assert_eq!(lines.len(), 20);
+        assert_eq!(lines[..4], ["467..114..", "ppp  nnn", "...*......", "   g"]);
+        assert_eq!(lines[16..18], ["...$.*....", "   s g"]);
+
+        let ansi = render::render(&parse("7*\n", &rules), &rules, render::Style::Ansi);
+        assert_eq!(ansi, "\x1b[32m7\x1b[0m\x1b[36m*\x1b[0m\n");
+    }
+
+    #[test]
+    fn test_clusters() {
+        let rules = Rules::default();
+        let schematic = parse(SAMPLE_1, &rules);
+        let star = schematic
+            .items
+            .iter()
+            .position(|i| i.loc() == Loc { x: 3, y: 1 });
+        let near: Vec<_> = schematic
+            .neighbours(star.unwrap())
+            .map(|i| i.num())
+            .collect();
+        assert_eq!(
+            near.iter().flatten().map(|n| n.val).collect::<Vec<_>>(),
+            [467, 35]
+        );
+        assert!(schematic.neighbours(0).all(|i| i.sym().is_some()));
+
+        let sums: Vec<_> = schematic
+            .clusters()
+            .iter()
+            .filter(|c| !c.syms.is_empty())
+            .map(|c| c.sum())
+            .collect();
+        assert_eq!(sums, [467 + 35, 633, 617, 592, 755 + 598, 664]);
+
+        // Numbers shared between symbols join them into one cluster.
+        let schematic = parse("1*2#3.4\n.......\n$......", &rules);
+        let clusters: Vec<_> = schematic.clusters().iter().map(|c| c.to_string()).collect();
+        assert_eq!(clusters, ["*#: 1 + 2 + 3 = 6", "4 = 4", "$: 0"]);
+        assert_eq!(
+            report::report(&schematic, report::Report::Neighbours),
+            "1 at 0,0: *\n* at 0,1: 1 2\n2 at 0,2: * #\n# at 0,3: 2 3\n3 at 0,4: #\n"
+        );
+    }
 }
@@ -1,19 +1,26 @@
 use anyhow::{Context, Result};
+use std::collections::HashSet;
 use std::str::FromStr;
 
 fn main() {
-    let text = std::fs::read_to_string("day-4/input.txt").unwrap();
+    let config = aoc::Config::load("day-4").unwrap();
+    let text = config.input().unwrap();
+    let cascade = config
+        .param::<String>("cascade")
+        .unwrap()
+        .map_or(Cascade::Clamp, |c| c.parse().unwrap());
     let res1 = part_1::process(&text);
-    let res2 = part_2::process(&text);
-    println!("Part 1: {res1}");
-    println!("Part 2: {res2}");
+    let res2 = part_2::process(&text, cascade).unwrap();
+    config.answer(1, res1);
+    config.answer(2, res2);
 }
 
 #[allow(dead_code)]
 #[derive(Clone, Debug)]
 struct Card {
     id: u32,
-    win: Vec<u32>,
+    /// Winning numbers, as a set so each pick is checked in constant time.
+    win: HashSet<u32>,
     pick: Vec<u32>,
     copies: u32,
 }
@@ -33,14 +40,12 @@ impl FromStr for Card {
         let nums = iter.next().context("missing numbers")?;
         let mut iter = nums.splitn(2, "|");
 
-        let to_numbers = |text: &str| -> Vec<u32> {
-            text.trim()
-                .split_whitespace()
-                .flat_map(|s| s.parse())
-                .collect()
-        };
+        let to_numbers =
+            |text: &str| -> Vec<u32> { text.split_whitespace().flat_map(|s| s.parse()).collect() };
 
-        let win = to_numbers(iter.next().context("missing winners")?);
+        let win = to_numbers(iter.next().context("missing winners")?)
+            .into_iter()
+            .collect();
         let pick = to_numbers(iter.next().context("missing picked numbers")?);
 
         Ok(Self {
@@ -52,26 +57,60 @@ impl FromStr for Card {
     }
 }
 
+/// What a card does with copies won past the end of the table.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Cascade {
+    /// Win copies only of the cards that exist.
+    Clamp,
+    /// Fail the whole table.
+    Error,
+    /// Carry on from the first card. Copies landing on a card already played are
+    /// counted but do not win anything themselves.
+    Wrap,
+}
+
+impl FromStr for Cascade {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "clamp" => Ok(Self::Clamp),
+            "error" => Ok(Self::Error),
+            "wrap" => Ok(Self::Wrap),
+            _ => anyhow::bail!("unknown cascade `{s}`, expected `clamp`, `error` or `wrap`"),
+        }
+    }
+}
+
 impl Card {
-    fn matches(&self) -> Vec<u32> {
-        self.pick
-            .iter()
-            .filter(|p| self.win.contains(p))
-            .copied()
-            .collect()
+    fn matches(&self) -> impl Iterator<Item = u32> + '_ {
+        self.pick.iter().filter(|p| self.win.contains(p)).copied()
     }
 
     fn match_count(&self) -> u32 {
-        self.matches().len() as u32
+        self.matches().count() as u32
+    }
+}
+
+/// Checks the cards are numbered 1, 2, 3... in order, so a card's id is its
+/// position plus one.
+fn validate(cards: &[Card]) -> Result<()> {
+    for (i, card) in cards.iter().enumerate() {
+        anyhow::ensure!(
+            card.id as usize == i + 1,
+            "card {} found where card {} was expected",
+            card.id,
+            i + 1
+        );
     }
+    Ok(())
 }
 
 fn parse(text: &str) -> Vec<Card> {
     text.lines()
         .map(|l| l.trim())
         .filter(|l| !l.is_empty())
-        .map(|l| l.parse())
-        .flatten()
+        .flat_map(|l| l.parse())
         .collect()
 }
 
@@ -94,20 +133,33 @@ mod part_1 {
 }
 
 mod part_2 {
-    use crate::parse;
+    use crate::{parse, validate, Cascade};
+    use anyhow::Result;
 
-    pub fn process(text: &str) -> u32 {
+    pub fn process(text: &str, cascade: Cascade) -> Result<u32> {
         let mut cards = parse(text);
-        for (i, matches) in cards.clone().iter().map(|c| c.match_count()).enumerate() {
+        validate(&cards)?;
+
+        let len = cards.len();
+        let counts: Vec<u32> = cards.iter().map(|c| c.match_count()).collect();
+        for (i, matches) in counts.into_iter().enumerate() {
             let copies = cards[i].copies;
-            for j in 0..matches {
-                let index = i + j as usize + 1;
-                let card = &mut cards[index];
-                card.copies += copies;
+            for index in i + 1..=i + matches as usize {
+                let index = match cascade {
+                    Cascade::Clamp if index >= len => break,
+                    Cascade::Error if index >= len => anyhow::bail!(
+                        "card {} wins card {}, past the last card {len}",
+                        i + 1,
+                        index + 1
+                    ),
+                    Cascade::Wrap => index % len,
+                    _ => index,
+                };
+                cards[index].copies += copies;
             }
         }
 
-        cards.iter().map(|c| c.copies).sum()
+        Ok(cards.iter().map(|c| c.copies).sum())
     }
 }
 
@@ -132,7 +184,34 @@ mod tests {
 
     #[test]
     fn test_part_2() {
-        let res = part_2::process(SAMPLE);
+        let res = part_2::process(SAMPLE, Cascade::Clamp).unwrap();
         assert_eq!(res, 30);
     }
+
+    #[test]
+    fn test_matches() {
+        let card: Card = "Card 1: 5 3 5 9 | 9 1 5 5 4".parse().unwrap();
+        assert_eq!(card.matches().collect::<Vec<_>>(), [9, 5, 5]);
+        assert_eq!(card.match_count(), 3);
+
+        let win: Vec<String> = (0..5000).map(|n| (n * 3).to_string()).collect();
+        let pick: Vec<String> = (0..5000).map(|n| (n * 2).to_string()).collect();
+        let card: Card = format!("Card 1: {} | {}", win.join(" "), pick.join(" "))
+            .parse()
+            .unwrap();
+        assert_eq!(card.match_count(), 1667);
+    }
+
+    #[test]
+    fn test_cascade() {
+        let text = "Card 1: 1 2 | 3 4\nCard 2: 1 2 3 | 1 2 3";
+        assert_eq!(part_2::process(text, Cascade::Clamp).unwrap(), 2);
+        assert!(part_2::process(text, Cascade::Error).is_err());
+        // Card 2 wins cards 1, 2 and 1 again after wrapping round.
+        assert_eq!(part_2::process(text, Cascade::Wrap).unwrap(), 5);
+
+        let text = "Card 1: 1 | 2\nCard 3: 1 | 2";
+        assert!(part_2::process(text, Cascade::Clamp).is_err());
+        assert!("loop".parse::<Cascade>().is_err());
+    }
 }
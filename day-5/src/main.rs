@@ -7,11 +7,22 @@ use std::str::FromStr;
 use strum::EnumString;
 
 fn main() {
-    let text = std::fs::read_to_string("day-5/input.txt").unwrap();
+    let config = aoc::Config::load("day-5").unwrap();
+    let text = config.input().unwrap();
+
+    if config.repl() {
+        let (_, maps) = parse(&text, SingleSeedParser).unwrap();
+        aoc::repl::run("day-5", repl::USAGE, |cmd, args| {
+            repl::eval(&maps, cmd, args)
+        })
+        .unwrap();
+        return;
+    }
+
     let res1 = part_1::process(&text).unwrap();
-    println!("Part 1: {res1}");
+    config.answer(1, res1);
     let res2 = part_2::process(&text).unwrap();
-    println!("Part 2: {res2}");
+    config.answer(2, res2);
 }
 
 type ID = u64;
@@ -36,12 +47,14 @@ enum Category {
 }
 
 #[allow(dead_code)]
+#[derive(Debug)]
 struct RangeMap {
     org: Range<ID>,
     dst: Range<ID>,
 }
 
 #[allow(dead_code)]
+#[derive(Debug)]
 struct ConversionMap {
     src: Category,
     dst: Category,
@@ -114,7 +127,7 @@ struct SingleSeedParser;
 
 impl SeedParser for SingleSeedParser {
     fn parse_seeds(&self, line: &str) -> Result<Vec<Seed>> {
-        let ids = line.split(':').last().context("missing ids")?;
+        let ids = line.split(':').next_back().context("missing ids")?;
         ids.split_whitespace().map(|s| s.parse::<Seed>()).collect()
     }
 }
@@ -123,21 +136,15 @@ struct RangeSeedParser;
 
 impl SeedParser for RangeSeedParser {
     fn parse_seeds(&self, line: &str) -> Result<Vec<Seed>> {
-        let ids = line.split(':').last().context("missing ids")?;
+        let ids = line.split(':').next_back().context("missing ids")?;
         let ids = ids
             .split_whitespace()
             .flat_map(|s| s.parse::<ID>())
             .batching(|it| match it.next() {
                 None => None,
-                Some(x) => match it.next() {
-                    None => None,
-                    Some(y) => Some((x, y)),
-                },
+                Some(x) => it.next().map(|y| (x, y)),
             });
-        Ok(ids
-            .map(|(id, len)| id..id + len)
-            .map(|r| Seed::Group(r))
-            .collect())
+        Ok(ids.map(|(id, len)| id..id + len).map(Seed::Group).collect())
     }
 }
 
@@ -149,8 +156,8 @@ fn parse_maps<'a>(lines: impl Iterator<Item = &'a str>) -> Result<ConversionMaps
     let mut conv: Option<ConversionMap> = None;
     for l in lines {
         if let Some(cats) = cat_rgx.captures(l) {
-            let src = (&cats["src"]).parse::<Category>()?;
-            let dst = (&cats["dst"]).parse::<Category>()?;
+            let src = cats["src"].parse::<Category>()?;
+            let dst = cats["dst"].parse::<Category>()?;
             if let Some(active) = conv {
                 res.push(active);
             }
@@ -158,9 +165,9 @@ fn parse_maps<'a>(lines: impl Iterator<Item = &'a str>) -> Result<ConversionMaps
         }
 
         if let Some(range) = range_rgx.captures(l) {
-            let src = (&range["src"]).parse::<ID>()?;
-            let dst = (&range["dst"]).parse::<ID>()?;
-            let len = (&range["len"]).parse::<ID>()?;
+            let src = range["src"].parse::<ID>()?;
+            let dst = range["dst"].parse::<ID>()?;
+            let len = range["len"].parse::<ID>()?;
             let org = src..src + len;
             let dst = dst..dst + len;
             let map = RangeMap { org, dst };
@@ -220,6 +227,36 @@ mod part_2 {
     }
 }
 
+mod repl {
+    use super::*;
+
+    pub const USAGE: &str = "resolve <category> <id>    follow an id through every map
+map <category>             show the map from a category";
+
+    pub fn eval(maps: &ConversionMaps, cmd: &str, args: &[&str]) -> Result<String> {
+        match (cmd, args) {
+            ("resolve", [cat, id]) => {
+                let mut cat: Category = cat.parse()?;
+                let mut id: ID = id.parse()?;
+                let mut steps = vec![format!("{cat:?} {id}")];
+                while let Some(map) = maps.map_from(cat) {
+                    id = map.resolve(id);
+                    cat = map.dst;
+                    steps.push(format!("{cat:?} {id}"));
+                }
+                Ok(steps.join(" -> "))
+            }
+            ("map", [cat]) => {
+                let map = maps
+                    .map_from(cat.parse()?)
+                    .context("no map from category")?;
+                Ok(format!("{map:#?}"))
+            }
+            _ => anyhow::bail!("unknown command `{cmd}`, try `help`"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -271,4 +308,14 @@ mod tests {
         let res = part_2::process(SAMPLE).unwrap();
         assert_eq!(res, 46);
     }
+
+    #[test]
+    fn test_repl_resolve() {
+        let (_, maps) = parse(SAMPLE, SingleSeedParser).unwrap();
+        let res = repl::eval(&maps, "resolve", &["seed", "79"]).unwrap();
+        assert_eq!(
+            res,
+            "Seed 79 -> Soil 81 -> Fertilizer 81 -> Water 81 -> Light 74 -> Temperature 78 -> Humidity 78 -> Location 82"
+        );
+    }
 }
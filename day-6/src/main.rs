@@ -2,15 +2,18 @@ use anyhow::Context;
 use anyhow::Result;
 use std::str::FromStr;
 
-fn main() {
-    let input = r#"
+const INPUT: &str = r#"
     Time:        56     71     79     99
     Distance:   334   1135   1350   2430
     "#;
-    let res1 = part_1::process(input).unwrap();
-    let res2 = part_2::process(input).unwrap();
-    println!("Part 1: {res1}");
-    println!("Part 2: {res2}");
+
+fn main() {
+    let config = aoc::Config::load("day-6").unwrap();
+    let input = config.input_or(INPUT).unwrap();
+    let res1 = part_1::process(&input).unwrap();
+    let res2 = part_2::process(&input).unwrap();
+    config.answer(1, res1);
+    config.answer(2, res2);
 }
 
 #[allow(dead_code)]
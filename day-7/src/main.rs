@@ -4,48 +4,48 @@ use std::cmp::Ordering;
 use strum::Display;
 
 fn main() {
-    let text = std::fs::read_to_string("day-7/input.txt").unwrap();
-    let res = process(&text);
+    let config = aoc::Config::load("day-7").unwrap();
+    let text = config.input().unwrap();
+    let joker = Card(config.param_or("joker", 'J').unwrap());
+
+    if config.repl() {
+        aoc::repl::run("day-7", repl::USAGE, |cmd, args| {
+            repl::eval(joker, cmd, args)
+        })
+        .unwrap();
+        return;
+    }
 
-    #[cfg(not(feature = "joker"))]
-    println!("Part 1: {res}");
+    let res = process(&text, Rules::Standard);
+    config.answer(1, res);
 
-    #[cfg(feature = "joker")]
-    println!("Part 2: {res}");
+    let res = process(&text, Rules::Joker(joker));
+    config.answer(2, res);
 }
 
-#[cfg(feature = "joker")]
-const CARD_ORDER: &str = "AKQT98765432J";
-
-#[cfg(not(feature = "joker"))]
 const CARD_ORDER: &str = "AKQJT98765432";
 
-#[cfg(feature = "joker")]
-const JOKER: Card = Card('J');
-
 #[derive(Copy, Clone, Debug, Eq, PartialEq)]
 struct Card(char);
 
-impl Card {
-    fn order(&self) -> usize {
-        CARD_ORDER.find(self.0).expect("Not a legal card")
-    }
-
-    #[cfg(feature = "joker")]
-    fn is_joker(&self) -> bool {
-        *self == JOKER
-    }
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq)]
+enum Rules {
+    #[default]
+    Standard,
+    /// The card is the weakest on its own but stands in for any other when building a kind.
+    Joker(Card),
 }
 
-impl PartialOrd<Self> for Card {
-    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
-        Some(self.cmp(other))
+impl Rules {
+    fn order(&self, card: &Card) -> usize {
+        match self {
+            Rules::Joker(joker) if joker == card => CARD_ORDER.len(),
+            _ => CARD_ORDER.find(card.0).expect("Not a legal card"),
+        }
     }
-}
 
-impl Ord for Card {
-    fn cmp(&self, other: &Self) -> Ordering {
-        self.order().cmp(&other.order())
+    fn is_joker(&self, card: &Card) -> bool {
+        matches!(self, Rules::Joker(joker) if joker == card)
     }
 }
 
@@ -61,20 +61,24 @@ enum Kind {
 }
 
 impl Kind {
-    fn build(cards: &[Card; 5]) -> Self {
-        let groups = cards.iter().sorted().rev().copied().group_by(|c| *c);
+    fn build(cards: &[Card; 5], rules: Rules) -> Self {
+        let groups = cards
+            .iter()
+            .sorted_by_key(|c| rules.order(c))
+            .rev()
+            .copied()
+            .group_by(|c| *c);
         let mut groups = groups
             .into_iter()
             .map(|(k, g)| (k, g.count()))
             .sorted_by(|lhs, rhs| rhs.1.cmp(&lhs.1))
             .collect_vec();
 
-        #[cfg(feature = "joker")]
         if groups.len() > 1 {
             let find = groups
                 .iter()
                 .enumerate()
-                .find(|(_, g)| g.0.is_joker())
+                .find(|(_, g)| rules.is_joker(&g.0))
                 .map(|(i, g)| (i, g.1));
 
             if let Some((i, count)) = find {
@@ -100,7 +104,7 @@ impl Kind {
 }
 
 #[derive(Debug, Eq, PartialEq)]
-struct Hand(Kind, [Card; 5]);
+struct Hand(Kind, [Card; 5], Rules);
 
 impl PartialOrd<Self> for Hand {
     fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
@@ -118,7 +122,7 @@ impl Ord for Hand {
         self.1
             .iter()
             .enumerate()
-            .map(|(i, c)| c.cmp(&other.1[i]))
+            .map(|(i, c)| self.2.order(c).cmp(&other.2.order(&other.1[i])))
             .find(|o| *o != Ordering::Equal)
             .unwrap_or(Ordering::Equal)
     }
@@ -133,47 +137,49 @@ struct Games(Vec<Game>);
 mod parse {
     use super::*;
     use anyhow::Context;
-    use std::str::FromStr;
 
-    impl FromStr for Hand {
-        type Err = anyhow::Error;
-        fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+    impl Hand {
+        pub fn parse(s: &str, rules: Rules) -> Result<Self> {
             anyhow::ensure!(s.len() == 5);
+            anyhow::ensure!(
+                s.chars()
+                    .all(|c| CARD_ORDER.contains(c) || rules.is_joker(&Card(c))),
+                "Not a legal card"
+            );
             let coll: [Card; 5] = s
                 .chars()
                 .map(Card)
                 .collect_vec()
                 .try_into()
                 .expect("5 cards");
-            let kind = Kind::build(&coll);
-            Ok(Self(kind, coll))
+            let kind = Kind::build(&coll, rules);
+            Ok(Self(kind, coll, rules))
         }
     }
 
-    impl FromStr for Game {
-        type Err = anyhow::Error;
-        fn from_str(s: &str) -> Result<Self> {
+    impl Game {
+        fn parse(s: &str, rules: Rules) -> Result<Self> {
             let mut iter = s.split_whitespace();
             let cards = iter.next().context("Cards")?;
             let bet = iter.next().context("Bet")?;
-            anyhow::ensure!(iter.next() == None);
-            Ok(Self(cards.parse()?, bet.parse()?))
+            anyhow::ensure!(iter.next().is_none());
+            Ok(Self(Hand::parse(cards, rules)?, bet.parse()?))
         }
     }
 
-    pub fn games(text: &str) -> Result<Games> {
+    pub fn games(text: &str, rules: Rules) -> Result<Games> {
         let games = text
             .lines()
             .map(|l| l.trim())
             .filter(|l| !l.is_empty())
-            .map(|l| l.parse::<Game>().unwrap())
+            .map(|l| Game::parse(l, rules).unwrap())
             .collect_vec();
         Ok(Games(games))
     }
 }
 
-fn process(text: &str) -> usize {
-    let games = parse::games(text).unwrap();
+fn process(text: &str, rules: Rules) -> usize {
+    let games = parse::games(text, rules).unwrap();
     games
         .0
         .iter()
@@ -184,6 +190,23 @@ fn process(text: &str) -> usize {
         .sum()
 }
 
+mod repl {
+    use super::*;
+
+    pub const USAGE: &str = "hand <cards>    show a hand under the standard and joker rules";
+
+    pub fn eval(joker: Card, cmd: &str, args: &[&str]) -> Result<String> {
+        match (cmd, args) {
+            ("hand", [cards]) => {
+                let standard = Hand::parse(cards, Rules::Standard)?;
+                let joker = Hand::parse(cards, Rules::Joker(joker))?;
+                Ok(format!("{standard:?}\n{joker:?}"))
+            }
+            _ => anyhow::bail!("unknown command `{cmd}`, try `help`"),
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -196,9 +219,15 @@ mod tests {
     QQQJA 483
     "#;
 
+    #[test]
+    fn test_part_1() {
+        let res = process(SAMPLE, Rules::Standard);
+        assert_eq!(res, 6440);
+    }
+
     #[test]
     fn test_part_2() {
-        let res = process(SAMPLE);
+        let res = process(SAMPLE, Rules::Joker(Card('J')));
         assert_eq!(res, 5905);
     }
 }
@@ -1,15 +1,32 @@
 use anyhow::{Context, Result};
+use graph::{Graph, NodeId};
 use num::integer;
 use rayon::prelude::*;
-use std::collections::HashMap;
 
 fn main() {
-    let text = std::fs::read_to_string("day-8/input.txt").unwrap();
-    let res = part_1::process(&text);
-    println!("Part 1: {res}");
+    let config = aoc::Config::load("day-8").unwrap();
+    let text = config.input().unwrap();
+
+    let start = config.param_or("start", "AAA".to_string()).unwrap();
+    let end = config.param_or("end", "ZZZ".to_string()).unwrap();
+
+    if config.repl() {
+        let lines = text.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
+        let map = Map::build(lines).unwrap();
+        aoc::repl::run("day-8", repl::USAGE, |cmd, args| {
+            repl::eval(&map, &start, cmd, args)
+        })
+        .unwrap();
+        return;
+    }
+
+    let res = part_1::process(&text, &start, &end);
+    config.answer(1, res);
 
-    let res = part_2::process(&text);
-    println!("Part 2: {res}");
+    let start = config.param_or("ghost_start", 'A').unwrap();
+    let end = config.param_or("ghost_end", 'Z').unwrap();
+    let res = part_2::process(&text, start, end);
+    config.answer(2, res);
 }
 
 #[derive(Debug)]
@@ -18,38 +35,44 @@ enum Direction {
     Right,
 }
 
+impl Direction {
+    /// Position of the branch among a node's edges: left is added first, right second.
+    fn branch(&self) -> usize {
+        match self {
+            Direction::Left => 0,
+            Direction::Right => 1,
+        }
+    }
+}
+
 #[derive(Debug)]
 struct Directions(Vec<Direction>);
 
 #[derive(Clone, Debug, Hash, Eq, PartialEq)]
 struct Key(String);
 
-#[derive(Clone, Debug)]
-struct Instruction(Key, Key);
+#[derive(Debug)]
+struct Instructions(Graph<Key>);
 
-impl Instruction {
-    fn next(&self, d: &Direction) -> &Key {
-        match d {
-            Direction::Left => &self.0,
-            Direction::Right => &self.1,
-        }
+impl Instructions {
+    fn node(&self, key: &Key) -> Option<NodeId> {
+        self.0.id(key)
     }
-}
 
-#[derive(Debug)]
-struct Instructions(HashMap<Key, Instruction>);
+    fn key(&self, id: NodeId) -> &Key {
+        self.0.node(id)
+    }
 
-impl<'a> Instructions {
-    fn path(&'a self, key: &Key) -> Option<&'a Instruction> {
-        self.0.get(key)
+    fn next(&self, id: NodeId, d: &Direction) -> Option<NodeId> {
+        self.0.edges(id).get(d.branch()).map(|(n, _)| *n)
     }
 
-    fn paths_ending(&'a self, key: char) -> Vec<Instruction> {
+    fn nodes_ending(&self, key: char) -> Vec<NodeId> {
         self.0
-            .iter()
-            .filter_map(|(k, v)| if k.0.ends_with(key) { Some(v) } else { None })
-            .cloned()
-            .collect::<Vec<_>>()
+            .nodes()
+            .filter(|(_, k)| k.0.ends_with(key))
+            .map(|(id, _)| id)
+            .collect()
     }
 }
 
@@ -62,11 +85,11 @@ struct Map {
 impl Map {
     fn navigate(&self, start: Key, end: Key) -> Result<u64> {
         let is_at_end = |k: &Key| -> bool { *k == end };
-        let start = self.inst.path(&start).context("first instruction")?;
+        let start = self.inst.node(&start).context("first instruction")?;
         self.navigate_end_count(start, &is_at_end)
     }
 
-    fn navigate_end_count<F>(&self, start: &Instruction, eval: &F) -> Result<u64>
+    fn navigate_end_count<F>(&self, start: NodeId, eval: &F) -> Result<u64>
     where
         F: FnOnce(&Key) -> bool + Copy,
     {
@@ -75,10 +98,9 @@ impl Map {
         let mut curr = start;
         loop {
             let d = dirs.next().context("next direction")?;
-            let n = curr.next(d);
-            curr = self.inst.path(n).context("next path")?;
+            curr = self.inst.next(curr, d).context("next path")?;
             count += 1;
-            if eval(n) {
+            if eval(self.inst.key(curr)) {
                 break;
             };
         }
@@ -87,15 +109,13 @@ impl Map {
 
     fn navigate_ends(&self, start: char, end: char) -> Result<u64> {
         let is_at_end = |k: &Key| -> bool { k.0.ends_with(end) };
-        let routes = self.inst.paths_ending(start);
+        let routes = self.inst.nodes_ending(start);
 
         let counts = routes
             .par_iter()
-            .flat_map(|i| self.navigate_end_count(i, &is_at_end));
+            .flat_map(|i| self.navigate_end_count(*i, &is_at_end));
 
-        counts
-            .reduce_with(|acc, i| integer::lcm(acc, i))
-            .context("")
+        counts.reduce_with(integer::lcm).context("")
     }
 }
 
@@ -135,15 +155,17 @@ mod parse {
             L: Iterator<Item = &'l str>,
         {
             let regex = Regex::new(INSTRUCTION_REGEX)?;
-            let mut map = HashMap::new();
+            let mut graph = Graph::new();
             for line in lines {
                 let caps = regex.captures(line).context("regex captures")?;
-                let key = Key((&caps["key"]).to_string());
-                let lhs = Key((&caps["left"]).to_string());
-                let rhs = Key((&caps["right"]).to_string());
-                map.insert(key, Instruction(lhs, rhs));
+                let key = graph.intern(Key(caps["key"].to_string()));
+                let lhs = graph.intern(Key(caps["left"].to_string()));
+                let rhs = graph.intern(Key(caps["right"].to_string()));
+                anyhow::ensure!(graph.edges(key).is_empty(), "duplicate node {line}");
+                graph.add_edge(key, lhs, 1);
+                graph.add_edge(key, rhs, 1);
             }
-            Ok(Self(map))
+            Ok(Self(graph))
         }
     }
 
@@ -167,21 +189,61 @@ mod parse {
 mod part_1 {
     use crate::{Key, Map};
 
-    pub fn process(s: &str) -> u64 {
+    pub fn process(s: &str, start: &str, end: &str) -> u64 {
         let lines = s.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
         let map = Map::build(lines).expect("map");
-        map.navigate(Key("AAA".to_string()), Key("ZZZ".to_string()))
-            .expect("path from AAA to ZZZ")
+        map.navigate(Key(start.to_string()), Key(end.to_string()))
+            .expect("path from start to end")
     }
 }
 
 mod part_2 {
     use crate::Map;
 
-    pub fn process(s: &str) -> u64 {
+    pub fn process(s: &str, start: char, end: char) -> u64 {
         let lines = s.lines().map(|l| l.trim()).filter(|l| !l.is_empty());
         let map = Map::build(lines).expect("map");
-        map.navigate_ends('A', 'Z').expect("path from AAA to ZZZ")
+        map.navigate_ends(start, end)
+            .expect("paths from starts to ends")
+    }
+}
+
+mod repl {
+    use super::*;
+
+    pub const USAGE: &str = "step <count> [from]    walk the directions from the start node
+node <key>             show the left and right branches of a node";
+
+    fn step(map: &Map, from: &str, count: &str) -> Result<String> {
+        let count: usize = count.parse()?;
+        let mut dirs = map.dir.0.iter().cycle();
+        let mut id = map
+            .inst
+            .node(&Key(from.to_string()))
+            .context("missing node")?;
+        let mut steps = Vec::new();
+        for i in 1..=count {
+            let d = dirs.next().context("next direction")?;
+            id = map.inst.next(id, d).context("missing node")?;
+            steps.push(format!("{i:>4} {d:?} -> {:?}", map.inst.key(id)));
+        }
+        Ok(steps.join("\n"))
+    }
+
+    pub fn eval(map: &Map, start: &str, cmd: &str, args: &[&str]) -> Result<String> {
+        match (cmd, args) {
+            ("step", [count]) => step(map, start, count),
+            ("step", [count, from]) => step(map, from, count),
+            ("node", [key]) => {
+                let id = map
+                    .inst
+                    .node(&Key(key.to_string()))
+                    .context("missing node")?;
+                let branches: Vec<_> = map.inst.0.neighbours(id).map(|n| map.inst.key(n)).collect();
+                Ok(format!("{branches:?}"))
+            }
+            _ => anyhow::bail!("unknown command `{cmd}`, try `help`"),
+        }
     }
 }
 
@@ -224,19 +286,19 @@ mod tests {
 
     #[test]
     fn test_part_1() {
-        let res = part_1::process(SAMPLE);
+        let res = part_1::process(SAMPLE, "AAA", "ZZZ");
         assert_eq!(res, 2);
     }
 
     #[test]
     fn test_part_1_sample_2() {
-        let res = part_1::process(SAMPLE_1_2);
+        let res = part_1::process(SAMPLE_1_2, "AAA", "ZZZ");
         assert_eq!(res, 6);
     }
 
     #[test]
     fn test_part_2_sample_1() {
-        let res = part_2::process(SAMPLE_2_1);
+        let res = part_2::process(SAMPLE_2_1, 'A', 'Z');
         assert_eq!(res, 6);
     }
 }
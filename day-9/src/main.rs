@@ -2,11 +2,12 @@ use color_eyre::Result;
 
 fn main() -> Result<()> {
     color_eyre::install()?;
-    let text = std::fs::read_to_string("day-9/input.txt")?;
+    let config = aoc::Config::load("day-9")?;
+    let text = config.input()?;
     let res = part_1::process(&text);
-    println!("Part 1: {res}");
+    config.answer(1, res);
     let res = part_2::process(&text);
-    println!("Part 2: {res}");
+    config.answer(2, res);
     Ok(())
 }
 
@@ -66,7 +67,7 @@ mod parse {
         text.lines()
             .map(|l| l.trim())
             .filter(|l| !l.is_empty())
-            .map(|l| Sequence::from(l))
+            .map(Sequence::from)
             .collect()
     }
 }
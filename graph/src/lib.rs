@@ -0,0 +1,162 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+
+mod search;
+
+pub use search::Bfs;
+
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash, Ord, PartialOrd)]
+pub struct NodeId(usize);
+
+impl NodeId {
+    pub fn index(&self) -> usize {
+        self.0
+    }
+}
+
+/// Directed graph over interned nodes.
+///
+/// Every distinct node value is stored once and addressed by its `NodeId`; edges
+/// keep the order they were added in, so callers may give positions a meaning
+/// (e.g. left/right branches). Undirected graphs add an edge each way.
+#[derive(Clone, Debug)]
+pub struct Graph<N, W = u64> {
+    nodes: Vec<N>,
+    index: HashMap<N, NodeId>,
+    edges: Vec<Vec<(NodeId, W)>>,
+}
+
+impl<N, W> Default for Graph<N, W> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            index: HashMap::new(),
+            edges: Vec::new(),
+        }
+    }
+}
+
+impl<N, W> Graph<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Copy,
+{
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn intern(&mut self, node: N) -> NodeId {
+        if let Some(id) = self.index.get(&node) {
+            return *id;
+        }
+
+        let id = NodeId(self.nodes.len());
+        self.nodes.push(node.clone());
+        self.edges.push(Vec::new());
+        self.index.insert(node, id);
+        id
+    }
+
+    pub fn id(&self, node: &N) -> Option<NodeId> {
+        self.index.get(node).copied()
+    }
+
+    pub fn node(&self, id: NodeId) -> &N {
+        &self.nodes[id.0]
+    }
+
+    pub fn nodes(&self) -> impl Iterator<Item = (NodeId, &N)> {
+        self.nodes.iter().enumerate().map(|(i, n)| (NodeId(i), n))
+    }
+
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    pub fn add_edge(&mut self, from: NodeId, to: NodeId, weight: W) {
+        self.edges[from.0].push((to, weight));
+    }
+
+    pub fn add_undirected_edge(&mut self, a: NodeId, b: NodeId, weight: W) {
+        self.add_edge(a, b, weight);
+        self.add_edge(b, a, weight);
+    }
+
+    /// Outgoing edges of a node, in insertion order.
+    pub fn edges(&self, id: NodeId) -> &[(NodeId, W)] {
+        &self.edges[id.0]
+    }
+
+    pub fn neighbours(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.edges[id.0].iter().map(|(n, _)| *n)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// a -> b -> c -> a, c -> d (weights 1, 2, 3, 10), plus an isolated pair e - f.
+    fn sample() -> Graph<char> {
+        let mut g = Graph::new();
+        let [a, b, c, d, e, f] = ['a', 'b', 'c', 'd', 'e', 'f'].map(|n| g.intern(n));
+        g.add_edge(a, b, 1);
+        g.add_edge(b, c, 2);
+        g.add_edge(c, a, 3);
+        g.add_edge(c, d, 10);
+        g.add_undirected_edge(e, f, 1);
+        g
+    }
+
+    fn names(g: &Graph<char>, ids: &[NodeId]) -> String {
+        ids.iter().map(|id| g.node(*id)).collect()
+    }
+
+    #[test]
+    fn test_intern() {
+        let mut g = sample();
+        assert_eq!(g.len(), 6);
+        assert_eq!(g.intern('c'), g.id(&'c').unwrap());
+        assert_eq!(g.len(), 6);
+        let c = g.id(&'c').unwrap();
+        assert_eq!(names(&g, &g.neighbours(c).collect::<Vec<_>>()), "ad");
+    }
+
+    #[test]
+    fn test_search() {
+        let g = sample();
+        let a = g.id(&'a').unwrap();
+
+        let bfs: Vec<_> = g.bfs(a).map(|(n, depth)| (*g.node(n), depth)).collect();
+        assert_eq!(bfs, vec![('a', 0), ('b', 1), ('c', 2), ('d', 3)]);
+        assert_eq!(names(&g, &g.dfs(a)), "abcd");
+
+        let dist = g.dijkstra(a);
+        assert_eq!(dist[g.id(&'d').unwrap().index()], Some(13));
+        assert_eq!(dist[g.id(&'e').unwrap().index()], None);
+
+        let (cost, path) = g.shortest_path(a, g.id(&'d').unwrap()).unwrap();
+        assert_eq!((cost, names(&g, &path).as_str()), (13, "abcd"));
+    }
+
+    #[test]
+    fn test_structure() {
+        let g = sample();
+        let cycle = g.find_cycle().unwrap();
+        assert_eq!(names(&g, &cycle), "abc");
+
+        let components: Vec<_> = g.components().iter().map(|c| names(&g, c)).collect();
+        assert_eq!(components, vec!["abcd", "ef"]);
+
+        let mut dag = Graph::<u32>::new();
+        let [x, y, z] = [1, 2, 3].map(|n| dag.intern(n));
+        dag.add_edge(x, y, 1);
+        dag.add_edge(x, z, 1);
+        dag.add_edge(y, z, 1);
+        assert_eq!(dag.find_cycle(), None);
+    }
+}
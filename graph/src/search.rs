@@ -0,0 +1,179 @@
+use super::*;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, VecDeque};
+use std::ops::Add;
+
+/// Breadth-first walk yielding every reachable node with its depth from the start.
+pub struct Bfs<'a, N, W> {
+    graph: &'a Graph<N, W>,
+    queue: VecDeque<(NodeId, usize)>,
+    seen: Vec<bool>,
+}
+
+impl<N, W> Iterator for Bfs<'_, N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Copy,
+{
+    type Item = (NodeId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (id, depth) = self.queue.pop_front()?;
+        for n in self.graph.neighbours(id) {
+            if !self.seen[n.0] {
+                self.seen[n.0] = true;
+                self.queue.push_back((n, depth + 1));
+            }
+        }
+        Some((id, depth))
+    }
+}
+
+#[derive(Copy, Clone, PartialEq)]
+enum Mark {
+    New,
+    Active,
+    Done,
+}
+
+impl<N, W> Graph<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Copy,
+{
+    pub fn bfs(&self, start: NodeId) -> Bfs<'_, N, W> {
+        let mut seen = vec![false; self.len()];
+        seen[start.0] = true;
+        Bfs {
+            graph: self,
+            queue: VecDeque::from([(start, 0)]),
+            seen,
+        }
+    }
+
+    /// Nodes reachable from `start` in depth-first pre-order, following edges in insertion order.
+    pub fn dfs(&self, start: NodeId) -> Vec<NodeId> {
+        let mut seen = vec![false; self.len()];
+        let mut order = Vec::new();
+        let mut stack = vec![start];
+        while let Some(id) = stack.pop() {
+            if seen[id.0] {
+                continue;
+            }
+            seen[id.0] = true;
+            order.push(id);
+            stack.extend(self.edges(id).iter().rev().map(|(n, _)| *n));
+        }
+        order
+    }
+
+    /// Any directed cycle, as the nodes along it. An undirected edge counts as a cycle of two.
+    pub fn find_cycle(&self) -> Option<Vec<NodeId>> {
+        let mut mark = vec![Mark::New; self.len()];
+        for root in 0..self.len() {
+            if mark[root] != Mark::New {
+                continue;
+            }
+
+            mark[root] = Mark::Active;
+            let mut stack = vec![(root, 0)];
+            while let Some((node, edge)) = stack.last_mut() {
+                let node = *node;
+                let Some((next, _)) = self.edges[node].get(*edge) else {
+                    mark[node] = Mark::Done;
+                    stack.pop();
+                    continue;
+                };
+                *edge += 1;
+
+                match mark[next.0] {
+                    Mark::New => {
+                        mark[next.0] = Mark::Active;
+                        stack.push((next.0, 0));
+                    }
+                    Mark::Active => {
+                        let from = stack.iter().position(|(n, _)| *n == next.0)?;
+                        return Some(stack[from..].iter().map(|(n, _)| NodeId(*n)).collect());
+                    }
+                    Mark::Done => {}
+                }
+            }
+        }
+        None
+    }
+
+    /// Weakly connected components, each listing its nodes in id order.
+    pub fn components(&self) -> Vec<Vec<NodeId>> {
+        fn root(parent: &mut [usize], mut x: usize) -> usize {
+            while parent[x] != x {
+                parent[x] = parent[parent[x]];
+                x = parent[x];
+            }
+            x
+        }
+
+        let mut parent: Vec<usize> = (0..self.len()).collect();
+        for (from, edges) in self.edges.iter().enumerate() {
+            for (to, _) in edges {
+                let (a, b) = (root(&mut parent, from), root(&mut parent, to.0));
+                parent[a.max(b)] = a.min(b);
+            }
+        }
+
+        let mut index = HashMap::new();
+        let mut components: Vec<Vec<NodeId>> = Vec::new();
+        for id in 0..self.len() {
+            let r = root(&mut parent, id);
+            let i = *index.entry(r).or_insert_with(|| {
+                components.push(Vec::new());
+                components.len() - 1
+            });
+            components[i].push(NodeId(id));
+        }
+        components
+    }
+}
+
+impl<N, W> Graph<N, W>
+where
+    N: Clone + Eq + Hash,
+    W: Copy + Ord + Default + Add<Output = W>,
+{
+    fn dijkstra_paths(&self, start: NodeId) -> (Vec<Option<W>>, Vec<Option<NodeId>>) {
+        let mut dist = vec![None; self.len()];
+        let mut prev = vec![None; self.len()];
+        let mut heap = BinaryHeap::from([Reverse((W::default(), start))]);
+        dist[start.0] = Some(W::default());
+
+        while let Some(Reverse((cost, id))) = heap.pop() {
+            if dist[id.0].is_some_and(|d| cost > d) {
+                continue;
+            }
+            for (next, weight) in self.edges(id) {
+                let cost = cost + *weight;
+                if dist[next.0].is_none_or(|d| cost < d) {
+                    dist[next.0] = Some(cost);
+                    prev[next.0] = Some(id);
+                    heap.push(Reverse((cost, *next)));
+                }
+            }
+        }
+        (dist, prev)
+    }
+
+    /// Cheapest distance from `start` to every node, indexed by `NodeId::index`.
+    pub fn dijkstra(&self, start: NodeId) -> Vec<Option<W>> {
+        self.dijkstra_paths(start).0
+    }
+
+    pub fn shortest_path(&self, start: NodeId, goal: NodeId) -> Option<(W, Vec<NodeId>)> {
+        let (dist, prev) = self.dijkstra_paths(start);
+        let cost = dist[goal.0]?;
+        let mut path = vec![goal];
+        while let Some(p) = prev[path[path.len() - 1].0] {
+            path.push(p);
+        }
+        path.reverse();
+        Some((cost, path))
+    }
+}
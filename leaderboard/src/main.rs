@@ -0,0 +1,274 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::str::FromStr;
+
+const DEFAULT_PATH: &str = "leaderboard.json";
+
+fn main() -> Result<()> {
+    let path = std::env::args()
+        .nth(1)
+        .unwrap_or_else(|| DEFAULT_PATH.to_string());
+    let text = std::fs::read_to_string(&path).with_context(|| format!("reading {path}"))?;
+    let board: Leaderboard = text.parse()?;
+
+    let standings = board.standings();
+    println!("{}", render::standings(&board, &standings));
+    println!("{}", render::deltas(&board, &standings));
+    Ok(())
+}
+
+type Timestamp = i64;
+type Day = u32;
+type Part = u32;
+
+#[derive(Debug, Deserialize)]
+struct Star {
+    get_star_ts: Timestamp,
+    #[serde(default)]
+    star_index: u64,
+}
+
+#[derive(Debug, Deserialize)]
+struct Member {
+    id: u64,
+    name: Option<String>,
+    stars: u32,
+    #[serde(default)]
+    completion_day_level: HashMap<Day, HashMap<Part, Star>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Leaderboard {
+    event: String,
+    members: HashMap<String, Member>,
+}
+
+#[derive(Debug)]
+struct Standing<'a> {
+    member: &'a Member,
+    score: u32,
+}
+
+impl FromStr for Leaderboard {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        serde_json::from_str(s).context("parsing leaderboard")
+    }
+}
+
+impl Member {
+    fn display_name(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => format!("(anonymous user #{})", self.id),
+        }
+    }
+
+    fn star(&self, day: Day, part: Part) -> Option<&Star> {
+        self.completion_day_level.get(&day)?.get(&part)
+    }
+
+    fn last_star(&self) -> Timestamp {
+        self.completion_day_level
+            .values()
+            .flat_map(|d| d.values())
+            .map(|s| s.get_star_ts)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Seconds between earning the first and the second star of a day.
+    fn delta(&self, day: Day) -> Option<Timestamp> {
+        let first = self.star(day, 1)?;
+        let second = self.star(day, 2)?;
+        Some(second.get_star_ts - first.get_star_ts)
+    }
+}
+
+impl Leaderboard {
+    fn days(&self) -> Vec<Day> {
+        self.members
+            .values()
+            .flat_map(|m| m.completion_day_level.keys())
+            .copied()
+            .collect::<BTreeSet<_>>()
+            .into_iter()
+            .collect()
+    }
+
+    /// Local score: for every star, the first member to earn it gets one point per
+    /// member on the board, the second one point less, and so on.
+    fn standings(&self) -> Vec<Standing<'_>> {
+        let count = self.members.len() as u32;
+        let mut scores: HashMap<u64, u32> = HashMap::new();
+
+        for day in self.days() {
+            for part in [1, 2] {
+                let mut finishers: Vec<_> = self
+                    .members
+                    .values()
+                    .filter_map(|m| m.star(day, part).map(|s| (m, s)))
+                    .collect();
+                finishers.sort_by_key(|(_, s)| (s.get_star_ts, s.star_index));
+
+                for (rank, (m, _)) in finishers.iter().enumerate() {
+                    *scores.entry(m.id).or_default() += count - rank as u32;
+                }
+            }
+        }
+
+        let mut standings: Vec<_> = self
+            .members
+            .values()
+            .map(|member| Standing {
+                member,
+                score: scores.get(&member.id).copied().unwrap_or(0),
+            })
+            .collect();
+        standings.sort_by_key(|s| {
+            (
+                Reverse(s.score),
+                Reverse(s.member.stars),
+                s.member.last_star(),
+                s.member.id,
+            )
+        });
+        standings
+    }
+}
+
+mod render {
+    use super::*;
+
+    fn hms(secs: Timestamp) -> String {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    }
+
+    fn stars(member: &Member, days: &[Day]) -> String {
+        days.iter()
+            .map(|d| match (member.star(*d, 1), member.star(*d, 2)) {
+                (Some(_), Some(_)) => '*',
+                (Some(_), None) => '+',
+                _ => '.',
+            })
+            .collect()
+    }
+
+    pub fn standings(board: &Leaderboard, standings: &[Standing]) -> String {
+        let days = board.days();
+        let mut out = format!("Leaderboard {}\n\n", board.event);
+        let w = days.len().max(4);
+        out += &format!(
+            "{:>3}  {:>5}  {:>5}  {:<w$}  Name\n",
+            "#", "Score", "Stars", "Days"
+        );
+        for (i, s) in standings.iter().enumerate() {
+            out += &format!(
+                "{:>3}  {:>5}  {:>5}  {:<w$}  {}\n",
+                i + 1,
+                s.score,
+                s.member.stars,
+                stars(s.member, &days),
+                s.member.display_name(),
+            );
+        }
+        out
+    }
+
+    pub fn deltas(board: &Leaderboard, standings: &[Standing]) -> String {
+        let days = board.days();
+        let mut out = String::from("Part 1 -> Part 2\n\n");
+        out += &format!("{:<24}", "Name");
+        for day in &days {
+            out += &format!(" {:>9}", format!("Day {day}"));
+        }
+        out += "\n";
+
+        for s in standings {
+            out += &format!("{:<24}", s.member.display_name());
+            for day in &days {
+                let delta = s.member.delta(*day).map(hms);
+                out += &format!(" {:>9}", delta.as_deref().unwrap_or("-"));
+            }
+            out += "\n";
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SAMPLE: &str = r#"{
+        "event": "2023",
+        "owner_id": 1,
+        "members": {
+            "1": {
+                "id": 1, "name": "alice", "stars": 4, "local_score": 0, "global_score": 0,
+                "last_star_ts": 1701410000,
+                "completion_day_level": {
+                    "1": {
+                        "1": { "get_star_ts": 1701410000, "star_index": 10 },
+                        "2": { "get_star_ts": 1701410600, "star_index": 12 }
+                    },
+                    "2": {
+                        "1": { "get_star_ts": 1701500000, "star_index": 30 },
+                        "2": { "get_star_ts": 1701503661, "star_index": 31 }
+                    }
+                }
+            },
+            "2": {
+                "id": 2, "name": null, "stars": 3, "local_score": 0, "global_score": 0,
+                "last_star_ts": 1701490000,
+                "completion_day_level": {
+                    "1": {
+                        "1": { "get_star_ts": 1701400000, "star_index": 5 },
+                        "2": { "get_star_ts": 1701420000, "star_index": 20 }
+                    },
+                    "2": {
+                        "1": { "get_star_ts": 1701490000, "star_index": 25 }
+                    }
+                }
+            },
+            "3": {
+                "id": 3, "name": "carol", "stars": 0, "local_score": 0, "global_score": 0,
+                "last_star_ts": 0,
+                "completion_day_level": {}
+            }
+        }
+    }"#;
+
+    #[test]
+    fn test_standings() {
+        let board: Leaderboard = SAMPLE.parse().unwrap();
+        let res: Vec<_> = board
+            .standings()
+            .iter()
+            .map(|s| (s.member.display_name(), s.score))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                ("alice".to_string(), 2 + 3 + 2 + 3),
+                ("(anonymous user #2)".to_string(), 3 + 2 + 3),
+                ("carol".to_string(), 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_deltas() {
+        let board: Leaderboard = SAMPLE.parse().unwrap();
+        let alice = &board.members["1"];
+        assert_eq!(alice.delta(1), Some(600));
+        assert_eq!(alice.delta(2), Some(3661));
+        assert_eq!(board.members["2"].delta(2), None);
+
+        let table = render::deltas(&board, &board.standings());
+        assert!(table.contains("   0:10:00   1:01:01"));
+    }
+}
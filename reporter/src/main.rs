@@ -0,0 +1,354 @@
+use anyhow::{Context, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+const REPORT_DIR: &str = "report";
+const ANSWERS_FILE: &str = "answers.txt";
+
+fn main() -> Result<()> {
+    let root = Path::new(".");
+    let days = workspace::days(root)?;
+    let results = runner::run(root, days)?;
+
+    let dir = root.join(REPORT_DIR);
+    std::fs::create_dir_all(&dir)?;
+    std::fs::write(dir.join("README.md"), render::markdown(&results))?;
+    std::fs::write(dir.join("index.html"), render::html(&results))?;
+
+    println!("Report: {} days written to {REPORT_DIR}/", results.len());
+    Ok(())
+}
+
+#[derive(Debug)]
+struct Day {
+    number: u32,
+    member: String,
+    package: String,
+}
+
+#[derive(Debug, PartialEq)]
+enum Status {
+    Verified,
+    Wrong(String),
+    Unverified,
+}
+
+#[derive(Debug)]
+struct Answer {
+    part: u32,
+    value: String,
+    status: Status,
+}
+
+#[derive(Debug)]
+enum Outcome {
+    Solved(Vec<Answer>),
+    Failed(String),
+}
+
+#[derive(Debug)]
+struct DayResult {
+    day: Day,
+    time: Duration,
+    outcome: Outcome,
+}
+
+impl Day {
+    fn source(&self) -> String {
+        format!("../{}/src/main.rs", self.member)
+    }
+}
+
+impl Answer {
+    fn cell(&self) -> String {
+        match &self.status {
+            Status::Verified => format!("{} ✅", self.value),
+            Status::Wrong(expected) => format!("{} ❌ (expected {expected})", self.value),
+            Status::Unverified => self.value.clone(),
+        }
+    }
+}
+
+impl DayResult {
+    fn answer(&self, part: u32) -> Option<&Answer> {
+        match &self.outcome {
+            Outcome::Solved(answers) => answers.iter().find(|a| a.part == part),
+            Outcome::Failed(_) => None,
+        }
+    }
+
+    fn status(&self) -> &'static str {
+        let Outcome::Solved(answers) = &self.outcome else {
+            return "failed";
+        };
+
+        if answers.iter().any(|a| matches!(a.status, Status::Wrong(_))) {
+            "wrong"
+        } else if !answers.is_empty() && answers.iter().all(|a| a.status == Status::Verified) {
+            "verified"
+        } else {
+            "unverified"
+        }
+    }
+
+    fn time(&self) -> String {
+        format!("{:.1} ms", self.time.as_secs_f64() * 1000.0)
+    }
+}
+
+mod workspace {
+    use super::*;
+    use toml::Table;
+
+    fn manifest(path: &Path) -> Result<Table> {
+        let text = std::fs::read_to_string(path.join("Cargo.toml"))
+            .with_context(|| format!("reading manifest in {}", path.display()))?;
+        Ok(text.parse()?)
+    }
+
+    pub fn days(root: &Path) -> Result<Vec<Day>> {
+        let workspace = manifest(root)?;
+        let members = workspace
+            .get("workspace")
+            .and_then(|w| w.get("members"))
+            .and_then(|m| m.as_array())
+            .context("missing workspace members")?;
+
+        let mut days = Vec::new();
+        for member in members.iter().filter_map(|m| m.as_str()) {
+            let Some(number) = member.strip_prefix("day-") else {
+                continue;
+            };
+
+            let package = manifest(&root.join(member))?
+                .get("package")
+                .and_then(|p| p.get("name"))
+                .and_then(|n| n.as_str())
+                .context("missing package name")?
+                .to_string();
+
+            days.push(Day {
+                number: number.parse()?,
+                member: member.to_string(),
+                package,
+            });
+        }
+
+        days.sort_by_key(|d| d.number);
+        Ok(days)
+    }
+}
+
+mod parse {
+    pub fn answers(text: &str) -> Vec<(u32, String)> {
+        text.lines()
+            .map(|l| l.trim())
+            .filter_map(|l| l.strip_prefix("Part "))
+            .filter_map(|l| l.split_once(':'))
+            .flat_map(|(part, value)| Some((part.trim().parse().ok()?, value.trim().to_string())))
+            .collect()
+    }
+}
+
+mod runner {
+    use super::*;
+
+    fn target_dir(root: &Path) -> PathBuf {
+        std::env::var_os("CARGO_TARGET_DIR")
+            .map(PathBuf::from)
+            .unwrap_or_else(|| root.join("target"))
+    }
+
+    fn build(root: &Path, days: &[Day]) -> Result<()> {
+        let mut cmd = Command::new("cargo");
+        cmd.current_dir(root).args(["build", "--release"]);
+        for day in days {
+            cmd.args(["-p", &day.package]);
+        }
+        anyhow::ensure!(cmd.status()?.success(), "failed to build days");
+        Ok(())
+    }
+
+    fn verify(day: &Day, root: &Path, answers: Vec<(u32, String)>) -> Result<Vec<Answer>> {
+        let path = root.join(&day.member).join(ANSWERS_FILE);
+        let expected = if path.exists() {
+            parse::answers(&std::fs::read_to_string(path)?)
+        } else {
+            Vec::new()
+        };
+
+        Ok(answers
+            .into_iter()
+            .map(|(part, value)| {
+                let status = match expected.iter().find(|(p, _)| *p == part) {
+                    Some((_, e)) if *e == value => Status::Verified,
+                    Some((_, e)) => Status::Wrong(e.clone()),
+                    None => Status::Unverified,
+                };
+                Answer {
+                    part,
+                    value,
+                    status,
+                }
+            })
+            .collect())
+    }
+
+    pub fn run(root: &Path, days: Vec<Day>) -> Result<Vec<DayResult>> {
+        build(root, &days)?;
+        let bin = target_dir(root).join("release");
+
+        let mut results = Vec::new();
+        for day in days {
+            let start = Instant::now();
+            let output = Command::new(bin.join(&day.package))
+                .current_dir(root)
+                .args(["--format", "text"])
+                .output()?;
+            let time = start.elapsed();
+
+            let outcome = if output.status.success() {
+                let answers = parse::answers(&String::from_utf8_lossy(&output.stdout));
+                Outcome::Solved(verify(&day, root, answers)?)
+            } else {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                let reason = stderr.lines().rev().find(|l| !l.trim().is_empty());
+                Outcome::Failed(reason.unwrap_or("unknown error").trim().to_string())
+            };
+
+            results.push(DayResult { day, time, outcome });
+        }
+
+        Ok(results)
+    }
+}
+
+mod render {
+    use super::*;
+
+    fn escape(s: &str) -> String {
+        s.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;")
+    }
+
+    fn cell(result: &DayResult, part: u32) -> String {
+        match (&result.outcome, result.answer(part)) {
+            (_, Some(answer)) => answer.cell(),
+            (Outcome::Failed(reason), None) if part == 1 => reason.clone(),
+            _ => "-".to_string(),
+        }
+    }
+
+    pub fn markdown(results: &[DayResult]) -> String {
+        let mut out = String::from("# Advent of Code 2023\n\n");
+        out += "| Day | Part 1 | Part 2 | Time | Status |\n";
+        out += "|---|---|---|---|---|\n";
+        for r in results {
+            out += &format!(
+                "| [{}]({}) | {} | {} | {} | {} |\n",
+                r.day.number,
+                r.day.source(),
+                cell(r, 1).replace('|', "\\|"),
+                cell(r, 2).replace('|', "\\|"),
+                r.time(),
+                r.status(),
+            );
+        }
+        out
+    }
+
+    pub fn html(results: &[DayResult]) -> String {
+        let rows: String = results
+            .iter()
+            .map(|r| {
+                format!(
+                    "      <tr class=\"{status}\"><td><a href=\"{src}\">Day {day}</a></td><td>{p1}</td><td>{p2}</td><td>{time}</td><td>{status}</td></tr>\n",
+                    status = r.status(),
+                    src = escape(&r.day.source()),
+                    day = r.day.number,
+                    p1 = escape(&cell(r, 1)),
+                    p2 = escape(&cell(r, 2)),
+                    time = r.time(),
+                )
+            })
+            .collect();
+
+        format!(
+            r#"<!DOCTYPE html>
+<html lang="en">
+  <head>
+    <meta charset="utf-8">
+    <title>Advent of Code 2023</title>
+    <style>
+      body {{ font-family: monospace; margin: 2em; }}
+      table {{ border-collapse: collapse; }}
+      th, td {{ border: 1px solid #999; padding: 0.3em 0.8em; }}
+      tr.verified {{ background: #e6ffe6; }}
+      tr.wrong, tr.failed {{ background: #ffe6e6; }}
+    </style>
+  </head>
+  <body>
+    <h1>Advent of Code 2023</h1>
+    <table>
+      <tr><th>Day</th><th>Part 1</th><th>Part 2</th><th>Time</th><th>Status</th></tr>
+{rows}    </table>
+  </body>
+</html>
+"#
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const OUTPUT: &str = r#"
+    Part 1: 8
+    Part 2: 2286
+    "#;
+
+    fn result(outcome: Outcome) -> DayResult {
+        DayResult {
+            day: Day {
+                number: 2,
+                member: "day-2".to_string(),
+                package: "day-2".to_string(),
+            },
+            time: Duration::from_millis(12),
+            outcome,
+        }
+    }
+
+    #[test]
+    fn test_parse_answers() {
+        let res = parse::answers(OUTPUT);
+        assert_eq!(res, vec![(1, "8".to_string()), (2, "2286".to_string())]);
+    }
+
+    #[test]
+    fn test_markdown() {
+        let answers = vec![
+            Answer {
+                part: 1,
+                value: "8".to_string(),
+                status: Status::Verified,
+            },
+            Answer {
+                part: 2,
+                value: "2286".to_string(),
+                status: Status::Wrong("2285".to_string()),
+            },
+        ];
+        let res = render::markdown(&[result(Outcome::Solved(answers))]);
+        assert!(res.contains(
+            "| [2](../day-2/src/main.rs) | 8 ✅ | 2286 ❌ (expected 2285) | 12.0 ms | wrong |"
+        ));
+
+        let res = render::html(&[result(Outcome::Failed("no <input>".to_string()))]);
+        assert!(res.contains("<td>no &lt;input&gt;</td><td>-</td>"));
+    }
+}